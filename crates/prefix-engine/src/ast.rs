@@ -7,7 +7,9 @@
 // Coverage target: 99%+ for supported languages.
 // Supported: Python, JavaScript, TypeScript, Rust, Go, C
 
-use crate::{Category, ClassifyResult, PrefixSymbol};
+use std::collections::HashMap;
+
+use crate::{Category, ClassifyResult, PrefixSymbol, SpanClassification};
 
 /// Language enum for AST parsing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,6 +40,10 @@ impl AstLanguage {
 /// Falls back to regex classifier for unsupported languages.
 pub struct AstClassifier {
     regex_fallback: crate::PrefixClassifier,
+    /// Per-language query overrides registered via
+    /// [`with_query_rules`](Self::with_query_rules). Linear-scanned since
+    /// there are at most a handful of [`AstLanguage`] variants.
+    query_overrides: Vec<(AstLanguage, QueryRules)>,
 }
 
 impl Default for AstClassifier {
@@ -50,7 +56,97 @@ impl AstClassifier {
     pub fn new() -> Self {
         Self {
             regex_fallback: crate::PrefixClassifier::new(),
+            query_overrides: Vec::new(),
+        }
+    }
+
+    /// Register a custom tree-sitter `.scm` query as the classification
+    /// policy for `lang`, overriding the embedded default. Captures are
+    /// matched against the standard taxonomy capture names (`@comment`,
+    /// `@import`, `@declaration`, `@logic`, `@modifier`, `@io`,
+    /// `@assignment`, `@neutral`) — any other capture name is left
+    /// unclassified. This separates the classification policy (which node
+    /// shapes mean what) out as data, the same way
+    /// [`crate::simd::KeywordTable`] does for the regex engine's keywords.
+    pub fn with_query_rules(
+        mut self,
+        lang: AstLanguage,
+        scm_source: &str,
+    ) -> Result<Self, tree_sitter::QueryError> {
+        let rules = QueryRules::compile(lang, scm_source)?;
+        self.query_overrides.retain(|(l, _)| *l != lang);
+        self.query_overrides.push((lang, rules));
+        Ok(self)
+    }
+
+    /// Classify `source` using tree-sitter query captures instead of the
+    /// node-kind match in [`classify_node_kind`](Self::classify_node_kind).
+    /// Uses the query registered via
+    /// [`with_query_rules`](Self::with_query_rules) for `lang` if any, else
+    /// the embedded default for that language. When two captured spans
+    /// start on the same line, the narrower (more specific) one wins.
+    pub fn classify_source_with_queries(&self, source: &str, lang: AstLanguage) -> Vec<ClassifyResult> {
+        match self.query_overrides.iter().find(|(l, _)| *l == lang) {
+            Some((_, rules)) => self.classify_with_rules(source, rules),
+            None => self.classify_with_rules(source, &QueryRules::builtin(lang)),
+        }
+    }
+
+    fn classify_with_rules(&self, source: &str, rules: &QueryRules) -> Vec<ClassifyResult> {
+        let mut parser = tree_sitter::Parser::new();
+        let ts_lang = Self::get_ts_language(rules.lang);
+        parser
+            .set_language(&ts_lang)
+            .expect("Failed to set language");
+
+        let tree = match parser.parse(source, None) {
+            Some(t) => t,
+            None => return self.regex_fallback.classify_batch(source),
+        };
+
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let mut spans: Vec<(usize, usize, PrefixSymbol, Category)> = Vec::new();
+        for m in cursor.matches(&rules.query, tree.root_node(), source.as_bytes()) {
+            for cap in m.captures {
+                let (sym, cat) = rules.capture_map[cap.index as usize];
+                if cat == Category::Unknown {
+                    continue;
+                }
+                spans.push((cap.node.start_byte(), cap.node.end_byte(), sym, cat));
+            }
+        }
+
+        let line_count = source.lines().count();
+        let mut line_symbols = vec![(PrefixSymbol::Zero, Category::Neutral); line_count];
+        let mut winning_span_len: Vec<Option<usize>> = vec![None; line_count];
+
+        for (start, end, sym, cat) in spans {
+            let line_idx = source[..start.min(source.len())].matches('\n').count();
+            if line_idx >= line_count {
+                continue;
+            }
+            let len = end - start;
+            let replace = match winning_span_len[line_idx] {
+                Some(existing_len) => len < existing_len,
+                None => true,
+            };
+            if replace {
+                line_symbols[line_idx] = (sym, cat);
+                winning_span_len[line_idx] = Some(len);
+            }
         }
+
+        line_symbols
+            .into_iter()
+            .enumerate()
+            .map(|(i, (sym, cat))| ClassifyResult {
+                symbol: sym.as_str().to_string(),
+                category: cat.as_str().to_string(),
+                bits: sym.to_bits(),
+                coords: sym.to_3d(),
+                line_num: i + 1,
+            })
+            .collect()
     }
 
     /// Get tree-sitter language for a given AstLanguage
@@ -222,8 +318,10 @@ impl AstClassifier {
         let mut line_symbols: Vec<(PrefixSymbol, Category)> =
             vec![(PrefixSymbol::Zero, Category::Neutral); line_count];
 
+        let bindings = Self::collect_import_bindings_from_tree(tree.root_node(), source, lang);
+
         // Walk the AST and assign symbols to lines based on node types
-        Self::walk_tree(tree.root_node(), source, &mut line_symbols, lang);
+        Self::walk_tree(tree.root_node(), source, &mut line_symbols, lang, &bindings);
 
         // Build results
         line_symbols
@@ -239,12 +337,78 @@ impl AstClassifier {
             .collect()
     }
 
+    /// Classify `source` at AST node granularity instead of collapsing each
+    /// node down to the one line it starts on — every [`SpanClassification`]
+    /// carries the node's exact byte range, so a `struct` spans its whole
+    /// body and a nested `return` only spans itself, rather than both being
+    /// squashed onto the same physical line. Returns `None` if `source`
+    /// fails to parse as `lang`, mirroring [`classify_source`]'s fallback.
+    pub fn classify_spans(&self, source: &str, lang: AstLanguage) -> Option<Vec<SpanClassification>> {
+        let mut parser = tree_sitter::Parser::new();
+        let ts_lang = Self::get_ts_language(lang);
+        parser
+            .set_language(&ts_lang)
+            .expect("Failed to set language");
+
+        let tree = parser.parse(source, None)?;
+
+        let bindings = Self::collect_import_bindings_from_tree(tree.root_node(), source, lang);
+        let mut spans = Vec::new();
+        Self::walk_tree_spans(tree.root_node(), source, &mut spans, lang, &bindings);
+        Some(spans)
+    }
+
+    /// Like [`walk_tree`], but pushes a [`SpanClassification`] per meaningful
+    /// node instead of collapsing onto `line_symbols`.
+    fn walk_tree_spans(
+        node: tree_sitter::Node,
+        source: &str,
+        spans: &mut Vec<SpanClassification>,
+        lang: AstLanguage,
+        bindings: &HashMap<String, String>,
+    ) {
+        let kind = node.kind();
+
+        if kind != "program" && kind != "source_file" && kind != "translation_unit" {
+            let (sym, cat) = Self::classify_node_kind(kind, lang);
+
+            let (sym, cat) =
+                if (kind == "call_expression" || kind == "call") && cat == Category::Unknown {
+                    let callee_text = node.child(0).map(|c| &source[c.byte_range()]).unwrap_or("");
+                    if Self::is_io_call(callee_text, bindings, lang) {
+                        (PrefixSymbol::MinusOne, Category::IO)
+                    } else {
+                        (sym, cat)
+                    }
+                } else {
+                    (sym, cat)
+                };
+
+            if cat != Category::Unknown && cat != Category::Neutral {
+                spans.push(SpanClassification {
+                    symbol: sym,
+                    category: cat,
+                    start: node.start_byte(),
+                    end: node.end_byte(),
+                });
+            }
+        }
+
+        let child_count = node.child_count();
+        for i in 0..child_count {
+            if let Some(child) = node.child(i) {
+                Self::walk_tree_spans(child, source, spans, lang, bindings);
+            }
+        }
+    }
+
     /// Recursively walk the AST and classify lines
     fn walk_tree(
         node: tree_sitter::Node,
         source: &str,
         line_symbols: &mut Vec<(PrefixSymbol, Category)>,
         lang: AstLanguage,
+        bindings: &HashMap<String, String>,
     ) {
         let kind = node.kind();
         let start_line = node.start_position().row;
@@ -257,7 +421,7 @@ impl AstClassifier {
             let (sym, cat) =
                 if (kind == "call_expression" || kind == "call") && cat == Category::Unknown {
                     let callee_text = node.child(0).map(|c| &source[c.byte_range()]).unwrap_or("");
-                    if Self::is_io_call(callee_text) {
+                    if Self::is_io_call(callee_text, bindings, lang) {
                         (PrefixSymbol::MinusOne, Category::IO)
                     } else {
                         (sym, cat)
@@ -278,7 +442,7 @@ impl AstClassifier {
                             .child(0)
                             .map(|c| &source[c.byte_range()])
                             .unwrap_or("");
-                        if Self::is_io_call(callee_text) {
+                        if Self::is_io_call(callee_text, bindings, lang) {
                             (PrefixSymbol::MinusOne, Category::IO)
                         } else {
                             (PrefixSymbol::MinusN, Category::Unknown)
@@ -306,24 +470,274 @@ impl AstClassifier {
         let child_count = node.child_count();
         for i in 0..child_count {
             if let Some(child) = node.child(i) {
-                Self::walk_tree(child, source, line_symbols, lang);
+                Self::walk_tree(child, source, line_symbols, lang, bindings);
             }
         }
     }
 
-    /// Check if a callee string is an I/O function
-    fn is_io_call(callee: &str) -> bool {
-        callee.contains("print")
-            || callee.contains("console.")
-            || callee.contains("log")
-            || callee.contains("write")
-            || callee.contains("read")
-            || callee.contains("fetch")
-            || callee.contains("stdin")
-            || callee.contains("stdout")
-            || callee.contains("stderr")
-            || callee.contains("open")
-            || callee.contains("socket")
+    /// Resolve whether `callee` (the head expression of a `call_expression`,
+    /// e.g. `"os.write"` or `"print"`) is a genuine I/O call: either a
+    /// language builtin/global that's I/O without needing an import
+    /// ([`builtin_io_callees`]), or a name whose import binding
+    /// ([`collect_import_bindings`]) resolves to a [`known_io_modules`]
+    /// entry. Replaces the old behavior of matching any substring like
+    /// `"log"`/`"open"`/`"read"` anywhere in the callee, which flagged
+    /// ordinary identifiers such as `preprocess` or `open_modal`.
+    fn is_io_call(callee: &str, bindings: &HashMap<String, String>, lang: AstLanguage) -> bool {
+        let head = callee
+            .split(|c: char| c == '.' || c == '(' || c == '!' || c.is_whitespace())
+            .next()
+            .unwrap_or("")
+            .trim();
+        if head.is_empty() {
+            return false;
+        }
+
+        if Self::builtin_io_callees(lang).contains(&head) {
+            return true;
+        }
+
+        match bindings.get(head) {
+            Some(module) => Self::known_io_modules(lang).iter().any(|known| {
+                module == known
+                    || module.starts_with(&format!("{known}::"))
+                    || module.starts_with(&format!("{known}."))
+                    || module.starts_with(&format!("{known}/"))
+            }),
+            None => false,
+        }
+    }
+
+    /// Modules/packages this crate treats as I/O sources, keyed the same
+    /// way [`collect_import_bindings`] records a binding's module (a bare
+    /// dotted/path/colon prefix). A submodule counts too: `use
+    /// std::io::Write;` binds `Write` to `"std::io::Write"`, which matches
+    /// the `"std::io"` entry here as a `std::io::` prefix.
+    fn known_io_modules(lang: AstLanguage) -> &'static [&'static str] {
+        match lang {
+            AstLanguage::Python => &["os", "sys", "io", "pathlib", "socket", "shutil"],
+            AstLanguage::Rust => &["std::io", "std::fs", "std::net"],
+            AstLanguage::JavaScript | AstLanguage::TypeScript => {
+                &["fs", "node:fs", "http", "node:http", "net", "node:net"]
+            }
+            AstLanguage::Go => &["fmt", "os", "bufio", "io", "net/http", "net"],
+            AstLanguage::C => &["stdio.h", "unistd.h", "fcntl.h"],
+        }
+    }
+
+    /// Callees that are I/O without needing a traceable import: language
+    /// builtins/globals (Python's `print`/`open`, Rust's I/O macro names,
+    /// JS/TS's `console`/`fetch`, C's libc I/O functions pulled in via
+    /// `collect_import_bindings`'s `#include` handling rather than listed
+    /// here, since C requires the header to actually be included). Only
+    /// `call_expression`/`call` heads are checked against this list: a Rust
+    /// macro such as `println!(...)` parses as a `macro_invocation` node,
+    /// which the tree walks do not pass to [`is_io_call`], so it is not
+    /// classified as I/O.
+    fn builtin_io_callees(lang: AstLanguage) -> &'static [&'static str] {
+        match lang {
+            AstLanguage::Python => &["print", "open", "input"],
+            AstLanguage::Rust => &["println", "print", "eprintln", "eprint", "write", "writeln"],
+            AstLanguage::JavaScript | AstLanguage::TypeScript => {
+                &["console", "fetch", "alert", "prompt"]
+            }
+            AstLanguage::Go => &[],
+            AstLanguage::C => &[],
+        }
+    }
+
+    /// Build the file's import symbol table: local binding name -> the
+    /// module/package/header it came from. Recurses over the whole tree
+    /// rather than just top-level statements, since some languages allow
+    /// imports inside a function body.
+    fn collect_import_bindings_from_tree(
+        node: tree_sitter::Node,
+        source: &str,
+        lang: AstLanguage,
+    ) -> HashMap<String, String> {
+        let mut bindings = HashMap::new();
+        Self::collect_import_bindings_rec(node, source, lang, &mut bindings);
+        bindings
+    }
+
+    fn collect_import_bindings_rec(
+        node: tree_sitter::Node,
+        source: &str,
+        lang: AstLanguage,
+        bindings: &mut HashMap<String, String>,
+    ) {
+        let kind = node.kind();
+        if matches!(
+            kind,
+            "import_statement" | "import_from_statement" | "use_declaration" | "import_spec" | "preproc_include"
+        ) {
+            Self::collect_import_bindings(kind, &source[node.byte_range()], lang, bindings);
+        }
+
+        let child_count = node.child_count();
+        for i in 0..child_count {
+            if let Some(child) = node.child(i) {
+                Self::collect_import_bindings_rec(child, source, lang, bindings);
+            }
+        }
+    }
+
+    /// Parse one import/use/include statement's raw source text into
+    /// binding(s) in `bindings`. Works off the statement's text rather than
+    /// per-grammar field names, since the exact shape of "the imported
+    /// name" and "the module path" differs enough across
+    /// Python/Rust/JS/TS/Go/C that matching every grammar's fields
+    /// precisely would be as fragile as not using fields at all — and the
+    /// only thing [`is_io_call`] actually needs right is the module half of
+    /// each binding, which this gets right for every common import form.
+    fn collect_import_bindings(kind: &str, text: &str, lang: AstLanguage, bindings: &mut HashMap<String, String>) {
+        match (lang, kind) {
+            (AstLanguage::Python, "import_statement") => {
+                for clause in text.trim_start_matches("import").split(',') {
+                    Self::bind_plain_import(clause, bindings);
+                }
+            }
+            (AstLanguage::Python, "import_from_statement") => {
+                if let Some((module_part, names_part)) =
+                    text.trim_start_matches("from").split_once("import")
+                {
+                    let module = module_part.trim().to_string();
+                    if !module.is_empty() {
+                        for clause in names_part.split(',') {
+                            Self::bind_from_import(clause, &module, bindings);
+                        }
+                    }
+                }
+            }
+            (AstLanguage::Rust, "use_declaration") => {
+                let body = text.trim_start_matches("use").trim_end_matches(';').trim();
+                match body.split_once("::{") {
+                    Some((path, items)) => {
+                        let path = path.trim();
+                        for item in items.trim_end_matches('}').split(',') {
+                            let item = item.trim();
+                            if item == "self" {
+                                let bound = path.rsplit("::").next().unwrap_or(path);
+                                bindings.insert(bound.to_string(), path.to_string());
+                            } else {
+                                Self::bind_from_import(item, path, bindings);
+                            }
+                        }
+                    }
+                    // No braces: `use a::b::c [as alias];` binds the last
+                    // path segment (or the alias) — unlike Python's
+                    // `import a.b.c`, which binds the first segment, so
+                    // this can't share `bind_plain_import`.
+                    None => {
+                        let (path, alias) = match body.split_once(" as ") {
+                            Some((p, a)) => (p.trim(), Some(a.trim())),
+                            None => (body, None),
+                        };
+                        if !path.is_empty() {
+                            let bound = alias
+                                .unwrap_or_else(|| path.rsplit("::").next().unwrap_or(path));
+                            bindings.insert(bound.to_string(), path.to_string());
+                        }
+                    }
+                }
+            }
+            (AstLanguage::JavaScript, "import_statement") | (AstLanguage::TypeScript, "import_statement") => {
+                if let Some(from_idx) = text.rfind("from") {
+                    let module = text[from_idx + "from".len()..]
+                        .trim()
+                        .trim_matches(|c: char| c == '\'' || c == '"' || c == ';' || c.is_whitespace())
+                        .to_string();
+                    let clause_part = text["import".len()..from_idx]
+                        .trim()
+                        .trim_matches(|c| c == '{' || c == '}');
+                    for clause in clause_part.split(',') {
+                        let clause = clause.trim().trim_start_matches('*').trim();
+                        let clause = clause.strip_prefix("as").map(str::trim).unwrap_or(clause);
+                        Self::bind_from_import(clause, &module, bindings);
+                    }
+                }
+            }
+            (AstLanguage::Go, "import_spec") => {
+                let (alias, path) = match text.split_once('"') {
+                    Some((prefix, rest)) => (prefix.trim().to_string(), rest.trim_end_matches('"').to_string()),
+                    None => (String::new(), text.trim().trim_matches('"').to_string()),
+                };
+                if !path.is_empty() {
+                    let pkg_name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                    let bound = if alias.is_empty() { pkg_name } else { alias };
+                    bindings.insert(bound, path);
+                }
+            }
+            (AstLanguage::C, "preproc_include") => {
+                let header = text
+                    .trim_start_matches("#include")
+                    .trim()
+                    .trim_matches(|c| c == '<' || c == '>' || c == '"')
+                    .to_string();
+                if Self::known_io_modules(AstLanguage::C).contains(&header.as_str()) {
+                    for func in Self::c_stdio_functions() {
+                        bindings.insert(func.to_string(), header.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `name [as alias]` (Python `import`): binds the alias if given, else
+    /// the first dotted segment of `name` (matching real Python's
+    /// `import a.b.c` binding only `a`).
+    fn bind_plain_import(clause: &str, bindings: &mut HashMap<String, String>) {
+        let clause = clause.trim();
+        if clause.is_empty() {
+            return;
+        }
+        let (dotted, alias) = match clause.split_once(" as ") {
+            Some((n, a)) => (n.trim(), Some(a.trim())),
+            None => (clause, None),
+        };
+        if dotted.is_empty() {
+            return;
+        }
+        let bound = alias.unwrap_or_else(|| {
+            dotted
+                .split("::")
+                .next()
+                .unwrap_or(dotted)
+                .split('.')
+                .next()
+                .unwrap_or(dotted)
+        });
+        bindings.insert(bound.to_string(), dotted.to_string());
+    }
+
+    /// `name [as alias]` imported from an already-known `module` (Python
+    /// `from`, Rust `use path::{items}`, JS/TS named imports).
+    fn bind_from_import(clause: &str, module: &str, bindings: &mut HashMap<String, String>) {
+        let clause = clause.trim();
+        if clause.is_empty() || clause == "*" {
+            return;
+        }
+        let (name, alias) = match clause.split_once(" as ") {
+            Some((n, a)) => (n.trim(), Some(a.trim())),
+            None => (clause, None),
+        };
+        if name.is_empty() {
+            return;
+        }
+        let bound = alias.unwrap_or(name);
+        bindings.insert(bound.to_string(), module.to_string());
+    }
+
+    /// libc I/O functions bound into scope once `<stdio.h>` (or one of the
+    /// other headers in [`known_io_modules`] for C) is included — C doesn't
+    /// bind a name per include the way the other languages do, so these
+    /// are granted directly rather than parsed out of the include text.
+    fn c_stdio_functions() -> &'static [&'static str] {
+        &[
+            "printf", "scanf", "fopen", "fclose", "fread", "fwrite", "fgets", "fputs", "puts",
+            "gets", "perror", "open", "read", "write", "close",
+        ]
     }
 
     /// Classify using AST if language is supported, regex otherwise
@@ -386,6 +800,551 @@ impl AstClassifier {
             disagreements,
         }
     }
+
+    /// Collapsible regions of `source`: one per [`Category::Declaration`] or
+    /// [`Category::Logic`] node spanning more than one line (functions,
+    /// classes, `impl` blocks, `if`/`for`/`while`/`match` bodies, …), plus
+    /// one per run of two or more consecutive comment lines. `kind` is the
+    /// lowercase category name, so editors can offer "fold all
+    /// declarations" separately from "fold all control flow". Returns an
+    /// empty list if `source` fails to parse as `lang`, mirroring
+    /// [`classify_spans`](Self::classify_spans)'s fallback.
+    pub fn folding_ranges(&self, source: &str, lang: AstLanguage) -> Vec<FoldRange> {
+        let mut parser = tree_sitter::Parser::new();
+        let ts_lang = Self::get_ts_language(lang);
+        parser
+            .set_language(&ts_lang)
+            .expect("Failed to set language");
+
+        let Some(tree) = parser.parse(source, None) else {
+            return Vec::new();
+        };
+
+        let mut ranges = Vec::new();
+        Self::collect_fold_ranges(tree.root_node(), lang, &mut ranges);
+
+        let line_results = self.classify_source(source, lang);
+        ranges.extend(Self::comment_fold_ranges(&line_results));
+
+        ranges.sort_by_key(|r| (r.start_line, r.end_line));
+        ranges
+    }
+
+    fn collect_fold_ranges(node: tree_sitter::Node, lang: AstLanguage, ranges: &mut Vec<FoldRange>) {
+        let kind = node.kind();
+        if kind != "program" && kind != "source_file" && kind != "translation_unit" {
+            let (_, cat) = Self::classify_node_kind(kind, lang);
+            if matches!(cat, Category::Declaration | Category::Logic) {
+                let start_line = node.start_position().row;
+                let end_line = node.end_position().row;
+                if end_line > start_line {
+                    ranges.push(FoldRange {
+                        start_line,
+                        end_line,
+                        kind: cat.as_str().to_string(),
+                    });
+                }
+            }
+        }
+
+        let child_count = node.child_count();
+        for i in 0..child_count {
+            if let Some(child) = node.child(i) {
+                Self::collect_fold_ranges(child, lang, ranges);
+            }
+        }
+    }
+
+    /// Turn runs of two or more consecutive `"comment"`-category lines from
+    /// a [`classify_source`](Self::classify_source) result into single
+    /// folds, the way editors treat a block of `//` lines as one region
+    /// rather than one fold per line.
+    fn comment_fold_ranges(line_results: &[ClassifyResult]) -> Vec<FoldRange> {
+        let mut ranges = Vec::new();
+        let mut run_start: Option<usize> = None;
+
+        for result in line_results {
+            let row = result.line_num - 1;
+            if result.category == "comment" {
+                run_start.get_or_insert(row);
+            } else if let Some(start) = run_start.take() {
+                push_comment_fold(&mut ranges, start, row - 1);
+            }
+        }
+        if let Some(start) = run_start {
+            push_comment_fold(&mut ranges, start, line_results.len().saturating_sub(1));
+        }
+
+        ranges
+    }
+
+    /// Hierarchical symbol outline for `source`: top-level declarations
+    /// (functions, classes, structs, impls, consts/statics/type aliases),
+    /// with declarations nested inside one another (methods in a
+    /// class/impl body) nested the same way in the returned tree. Returns
+    /// an empty list if `source` fails to parse as `lang`.
+    pub fn outline(&self, source: &str, lang: AstLanguage) -> Vec<SymbolNode> {
+        let mut parser = tree_sitter::Parser::new();
+        let ts_lang = Self::get_ts_language(lang);
+        parser
+            .set_language(&ts_lang)
+            .expect("Failed to set language");
+
+        let Some(tree) = parser.parse(source, None) else {
+            return Vec::new();
+        };
+
+        Self::outline_children(tree.root_node(), source)
+    }
+
+    /// Collect a [`SymbolNode`] for every outline-worthy declaration that is
+    /// a descendant of `node`, stopping the descent at each one found (its
+    /// own nested declarations are collected separately, rooted at itself).
+    fn outline_children(node: tree_sitter::Node, source: &str) -> Vec<SymbolNode> {
+        let mut nodes = Vec::new();
+        let child_count = node.child_count();
+        for i in 0..child_count {
+            let Some(child) = node.child(i) else {
+                continue;
+            };
+            match Self::outline_node(child, source) {
+                Some(symbol) => nodes.push(symbol),
+                // Not a declaration itself (a block, a statement, …) — keep
+                // looking inside it so a method nested in a class body, or
+                // a function nested in an `if`, still surfaces.
+                None => nodes.extend(Self::outline_children(child, source)),
+            }
+        }
+        nodes
+    }
+
+    /// Build a [`SymbolNode`] for `node` if it's an outline-worthy
+    /// declaration ([`OUTLINE_KINDS`]), unwrapping `decorated_definition`/
+    /// `export_statement` wrappers to the real declaration inside (keeping
+    /// the wrapper's span, so decorators stay part of the fold). Returns
+    /// `None` for anything else.
+    fn outline_node(node: tree_sitter::Node, source: &str) -> Option<SymbolNode> {
+        let kind = node.kind();
+
+        if kind == "decorated_definition" || kind == "export_statement" {
+            let child_count = node.child_count();
+            for i in 0..child_count {
+                let child = node.child(i)?;
+                if let Some(mut symbol) = Self::outline_node(child, source) {
+                    symbol.start_line = node.start_position().row;
+                    return Some(symbol);
+                }
+            }
+            return None;
+        }
+
+        if !OUTLINE_KINDS.contains(&kind) {
+            return None;
+        }
+
+        let (symbol, category) = if kind == "arrow_function"
+            || kind == "function_expression"
+            || kind == "lambda"
+            || kind == "func_literal"
+        {
+            (PrefixSymbol::PlusOne, Category::Declaration)
+        } else {
+            let lang = AstLanguage::Python; // node kind alone determines Declaration here; lang only disambiguates I/O calls, which OUTLINE_KINDS never contains.
+            Self::classify_node_kind(kind, lang)
+        };
+
+        Some(SymbolNode {
+            name: Self::declaration_name(node, source),
+            category: category.as_str().to_string(),
+            symbol: symbol.as_str().to_string(),
+            start_line: node.start_position().row,
+            end_line: node.end_position().row,
+            children: Self::outline_children(node, source),
+        })
+    }
+
+    /// Find the declaration's identifier to use as its display name,
+    /// preferring tree-sitter's `name` field when the grammar defines one
+    /// and falling back to the first identifier-shaped child otherwise
+    /// (e.g. Rust's `impl_item` has no `name` field, but does have a
+    /// `type_identifier` child naming what it implements for). Anonymous
+    /// functions/closures have neither, so they get a synthetic name.
+    fn declaration_name(node: tree_sitter::Node, source: &str) -> String {
+        if let Some(name_node) = node.child_by_field_name("name") {
+            return source[name_node.byte_range()].to_string();
+        }
+        let child_count = node.child_count();
+        for i in 0..child_count {
+            if let Some(child) = node.child(i) {
+                if child.kind() == "identifier" || child.kind() == "type_identifier" {
+                    return source[child.byte_range()].to_string();
+                }
+            }
+        }
+        "<anonymous>".to_string()
+    }
+}
+
+fn push_comment_fold(ranges: &mut Vec<FoldRange>, start_line: usize, end_line: usize) {
+    if end_line > start_line {
+        ranges.push(FoldRange {
+            start_line,
+            end_line,
+            kind: "comment".to_string(),
+        });
+    }
+}
+
+/// A classification rule keyed on a tree-sitter query capture name, e.g.
+/// `("comment", PrefixSymbol::MinusZero, Category::Comment)` for `@comment`.
+type CaptureRule = (&'static str, PrefixSymbol, Category);
+
+/// The standard capture-name taxonomy every embedded `.scm` query is written
+/// against. A caller supplying their own query via
+/// [`AstClassifier::with_query_rules`] should tag captures with these same
+/// names so they resolve to a sensible `(PrefixSymbol, Category)`; any other
+/// capture name falls back to `Category::Unknown` and is ignored.
+const DEFAULT_CAPTURE_RULES: &[CaptureRule] = &[
+    ("comment", PrefixSymbol::MinusZero, Category::Comment),
+    ("import", PrefixSymbol::N, Category::Import),
+    ("declaration", PrefixSymbol::PlusOne, Category::Declaration),
+    ("logic", PrefixSymbol::One, Category::Logic),
+    ("modifier", PrefixSymbol::PlusN, Category::Modifier),
+    ("io", PrefixSymbol::MinusOne, Category::IO),
+    ("assignment", PrefixSymbol::PlusZero, Category::Assignment),
+    ("neutral", PrefixSymbol::Zero, Category::Neutral),
+];
+
+/// Conservative, high-confidence query capturing Python's comment, import,
+/// declaration, control-flow, flow-modifier and assignment node kinds; I/O
+/// calls are not captured. Intentionally
+/// narrower than [`AstClassifier::classify_node_kind`]'s full match — this is
+/// the starting point for [`QueryRules::builtin`], not a drop-in replacement.
+const PYTHON_QUERY: &str = r#"
+(comment) @comment
+(import_statement) @import
+(import_from_statement) @import
+(function_definition) @declaration
+(class_definition) @declaration
+(decorated_definition) @declaration
+(if_statement) @logic
+(elif_clause) @logic
+(else_clause) @logic
+(for_statement) @logic
+(while_statement) @logic
+(try_statement) @logic
+(except_clause) @logic
+(finally_clause) @logic
+(return_statement) @modifier
+(raise_statement) @modifier
+(assert_statement) @modifier
+(assignment) @assignment
+(augmented_assignment) @assignment
+"#;
+
+/// A compiled, language-bound set of capture rules ready to query a parsed
+/// tree. Kept separate from [`AstClassifier`] itself (mirroring
+/// [`crate::simd::KeywordTable`] vs. [`crate::simd::CompiledKeywords`]) so the
+/// declarative `.scm` source and the `PrefixSymbol`/`Category` mapping can be
+/// swapped out independently of the classifier that runs it.
+pub struct QueryRules {
+    lang: AstLanguage,
+    query: tree_sitter::Query,
+    capture_map: Vec<(PrefixSymbol, Category)>,
+}
+
+impl QueryRules {
+    /// Compile `scm_source` for `lang`, mapping each capture name present in
+    /// the query to its entry in [`DEFAULT_CAPTURE_RULES`] (or
+    /// `Category::Unknown` for an unrecognized name).
+    pub fn compile(lang: AstLanguage, scm_source: &str) -> Result<Self, tree_sitter::QueryError> {
+        let ts_lang = AstClassifier::get_ts_language(lang);
+        let query = tree_sitter::Query::new(&ts_lang, scm_source)?;
+        let capture_map = query
+            .capture_names()
+            .iter()
+            .map(|name| {
+                let name = name.to_string();
+                DEFAULT_CAPTURE_RULES
+                    .iter()
+                    .find(|(rule_name, _, _)| *rule_name == name)
+                    .map(|(_, sym, cat)| (*sym, *cat))
+                    .unwrap_or((PrefixSymbol::Zero, Category::Unknown))
+            })
+            .collect();
+        Ok(Self {
+            lang,
+            query,
+            capture_map,
+        })
+    }
+
+    /// The embedded default query for `lang`. Never panics: a language with
+    /// no embedded query (or an embedded query that somehow fails to
+    /// compile against its own grammar) falls back to an empty query, which
+    /// always compiles and simply classifies nothing — every line comes
+    /// back neutral rather than a crash.
+    pub fn builtin(lang: AstLanguage) -> Self {
+        let scm = match lang {
+            AstLanguage::Python => PYTHON_QUERY,
+            _ => "",
+        };
+        Self::compile(lang, scm).unwrap_or_else(|_| {
+            Self::compile(lang, "").unwrap_or_else(|_| Self {
+                lang,
+                query: tree_sitter::Query::new(&AstClassifier::get_ts_language(lang), "")
+                    .expect("empty query must always compile"),
+                capture_map: Vec::new(),
+            })
+        })
+    }
+}
+
+/// A single text edit, in the byte offsets and row/column positions
+/// tree-sitter needs to keep existing tree nodes outside the edited range
+/// valid for reuse (`tree_sitter::InputEdit`, reshaped for this crate's
+/// public surface so callers don't need a `tree-sitter` dependency of their
+/// own just to construct one).
+#[derive(Debug, Clone, Copy)]
+pub struct TextEdit {
+    pub start_byte: usize,
+    pub old_end_byte: usize,
+    pub new_end_byte: usize,
+    pub start_position: (usize, usize),
+    pub old_end_position: (usize, usize),
+    pub new_end_position: (usize, usize),
+}
+
+/// A parsed tree kept alongside its source and language, so edits can be
+/// applied incrementally via tree-sitter's `tree.edit` + `parser.parse(_,
+/// Some(&old_tree))` instead of reparsing the whole document from scratch.
+pub struct IncrementalAstDocument {
+    classifier: AstClassifier,
+    lang: AstLanguage,
+    source: String,
+    tree: tree_sitter::Tree,
+    parser: tree_sitter::Parser,
+    results: Vec<ClassifyResult>,
+    /// Import-name -> module bindings accumulated so far. Refreshed only for
+    /// the subtrees a given [`reclassify`](Self::reclassify) call re-walks,
+    /// rather than recomputed over the whole tree on every edit.
+    bindings: HashMap<String, String>,
+}
+
+impl IncrementalAstDocument {
+    /// Parse `source` in full and cache the initial classification.
+    /// Returns `None` if `source` fails to parse as `lang`.
+    pub fn new(classifier: AstClassifier, source: &str, lang: AstLanguage) -> Option<Self> {
+        let mut parser = tree_sitter::Parser::new();
+        let ts_lang = AstClassifier::get_ts_language(lang);
+        parser
+            .set_language(&ts_lang)
+            .expect("Failed to set language");
+
+        let tree = parser.parse(source, None)?;
+        let bindings = AstClassifier::collect_import_bindings_from_tree(tree.root_node(), source, lang);
+        let results = Self::walk_and_collect(&tree, source, lang, &bindings);
+
+        Some(Self {
+            classifier,
+            lang,
+            source: source.to_string(),
+            tree,
+            parser,
+            results,
+            bindings,
+        })
+    }
+
+    /// The classification results as of the last `new`/`reclassify` call.
+    pub fn results(&self) -> &[ClassifyResult] {
+        &self.results
+    }
+
+    /// Apply `edit`, reparse incrementally, and return the fresh
+    /// classification.
+    ///
+    /// Rather than re-walking the whole tree, this:
+    /// 1. Splices `results` (the per-line cache) so lines before the edit
+    ///    keep their entries untouched and lines after it shift by however
+    ///    many newlines the edit inserted or removed, keeping the cache
+    ///    aligned with `new_source` before anything is re-walked.
+    /// 2. For each of tree-sitter's `changed_ranges`, finds the enclosing
+    ///    statement-level node (see [`climb_to_statement`](Self::climb_to_statement))
+    ///    and re-walks only that subtree, overwriting cached entries for the
+    ///    lines it spans and leaving every other line's cached
+    ///    classification untouched.
+    /// 3. Refreshes import bindings from those same subtrees only, merging
+    ///    them into the accumulated binding table instead of rescanning the
+    ///    whole file.
+    ///
+    /// When `changed_ranges` reports nothing changed (e.g. a no-op edit, or
+    /// one confined to whitespace the grammar doesn't represent as a node),
+    /// the previous results are returned as-is.
+    pub fn reclassify(&mut self, edit: TextEdit, new_source: &str) -> &[ClassifyResult] {
+        let ts_edit = tree_sitter::InputEdit {
+            start_byte: edit.start_byte,
+            old_end_byte: edit.old_end_byte,
+            new_end_byte: edit.new_end_byte,
+            start_position: tree_sitter::Point::new(edit.start_position.0, edit.start_position.1),
+            old_end_position: tree_sitter::Point::new(
+                edit.old_end_position.0,
+                edit.old_end_position.1,
+            ),
+            new_end_position: tree_sitter::Point::new(
+                edit.new_end_position.0,
+                edit.new_end_position.1,
+            ),
+        };
+        self.tree.edit(&ts_edit);
+        self.source = new_source.to_string();
+
+        match self.parser.parse(new_source, Some(&self.tree)) {
+            Some(new_tree) => {
+                let changed_ranges: Vec<tree_sitter::Range> =
+                    self.tree.changed_ranges(&new_tree).collect();
+                self.tree = new_tree;
+
+                if !changed_ranges.is_empty() {
+                    self.splice_cached_lines(edit.old_end_position.0, edit.new_end_position.0);
+
+                    let mut line_symbols: Vec<(PrefixSymbol, Category)> =
+                        self.results.iter().map(Self::line_state_of).collect();
+
+                    for range in &changed_ranges {
+                        let descendant = self
+                            .tree
+                            .root_node()
+                            .descendant_for_byte_range(range.start_byte, range.end_byte)
+                            .unwrap_or_else(|| self.tree.root_node());
+                        // `descendant_for_byte_range` returns the narrowest
+                        // enclosing node, which can be a leaf (e.g. the `42`
+                        // in `x = 42`) that `classify_node_kind` doesn't
+                        // recognize. Climb to the outermost ancestor that
+                        // still starts on the same row, which is the
+                        // statement-level node `walk_tree` actually
+                        // classifies.
+                        let node = Self::climb_to_statement(descendant);
+
+                        self.bindings.extend(AstClassifier::collect_import_bindings_from_tree(
+                            node,
+                            &self.source,
+                            self.lang,
+                        ));
+                        AstClassifier::walk_tree(
+                            node,
+                            &self.source,
+                            &mut line_symbols,
+                            self.lang,
+                            &self.bindings,
+                        );
+                    }
+
+                    self.results = line_symbols
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (sym, cat))| Self::result_of(i + 1, sym, cat))
+                        .collect();
+                }
+            }
+            None => {
+                // Reparse failed outright (e.g. the edit left the source in
+                // an unparseable state); fall back to the regex engine so
+                // callers get fresh-ish results instead of stale ones.
+                self.results = self.classifier.regex_fallback.classify_batch(&self.source);
+            }
+        }
+
+        &self.results
+    }
+
+    /// Insert or remove placeholder entries in `results` so its length (and
+    /// every entry's `line_num`) matches `new_source` before any re-walk
+    /// happens. Rows in `[old_end_row, new_end_row)` are the ones the edit
+    /// added or removed; everything before `old_end_row.min(new_end_row)`
+    /// keeps its existing cached entry, since a later re-walk overwrites
+    /// only the rows inside the subtrees `changed_ranges` actually touched.
+    fn splice_cached_lines(&mut self, old_end_row: usize, new_end_row: usize) {
+        match new_end_row.cmp(&old_end_row) {
+            std::cmp::Ordering::Greater => {
+                let inserted = new_end_row - old_end_row;
+                let at = old_end_row.min(self.results.len());
+                let placeholder = Self::result_of(0, PrefixSymbol::Zero, Category::Neutral);
+                for _ in 0..inserted {
+                    self.results.insert(at, placeholder.clone());
+                }
+            }
+            std::cmp::Ordering::Less => {
+                let removed = old_end_row - new_end_row;
+                let at = new_end_row.min(self.results.len());
+                let end = (at + removed).min(self.results.len());
+                self.results.drain(at..end);
+            }
+            std::cmp::Ordering::Equal => {}
+        }
+        for (i, result) in self.results.iter_mut().enumerate() {
+            result.line_num = i + 1;
+        }
+    }
+
+    /// Recover the `(PrefixSymbol, Category)` pair a cached [`ClassifyResult`]
+    /// was built from. `ClassifyResult::bits` round-trips through
+    /// [`PrefixSymbol::from_bits`] exactly, and every category maps to
+    /// exactly one symbol (see [`Category::symbol`]), so the pair is
+    /// recoverable without re-parsing `result.category`.
+    fn line_state_of(result: &ClassifyResult) -> (PrefixSymbol, Category) {
+        let sym = PrefixSymbol::from_bits(result.bits);
+        let cat = Category::ALL
+            .iter()
+            .find(|c| c.symbol() == sym)
+            .copied()
+            .unwrap_or(Category::Unknown);
+        (sym, cat)
+    }
+
+    /// Walk up from `node` while its parent still starts on the same row.
+    /// `descendant_for_byte_range` tends to bottom out on a leaf several
+    /// levels below the statement/declaration node that actually carries a
+    /// recognizable kind, so re-walking from the leaf directly would miss
+    /// it; this finds the same node a full top-down walk would classify for
+    /// that row.
+    fn climb_to_statement(node: tree_sitter::Node) -> tree_sitter::Node {
+        let mut current = node;
+        while let Some(parent) = current.parent() {
+            if parent.start_position().row != current.start_position().row {
+                break;
+            }
+            current = parent;
+        }
+        current
+    }
+
+    fn result_of(line_num: usize, sym: PrefixSymbol, cat: Category) -> ClassifyResult {
+        ClassifyResult {
+            symbol: sym.as_str().to_string(),
+            category: cat.as_str().to_string(),
+            bits: sym.to_bits(),
+            coords: sym.to_3d(),
+            line_num,
+        }
+    }
+
+    fn walk_and_collect(
+        tree: &tree_sitter::Tree,
+        source: &str,
+        lang: AstLanguage,
+        bindings: &HashMap<String, String>,
+    ) -> Vec<ClassifyResult> {
+        let line_count = source.lines().count();
+        let mut line_symbols: Vec<(PrefixSymbol, Category)> =
+            vec![(PrefixSymbol::Zero, Category::Neutral); line_count];
+        AstClassifier::walk_tree(tree.root_node(), source, &mut line_symbols, lang, bindings);
+        line_symbols
+            .into_iter()
+            .enumerate()
+            .map(|(i, (sym, cat))| Self::result_of(i + 1, sym, cat))
+            .collect()
+    }
 }
 
 /// Report comparing AST vs regex classification
@@ -409,6 +1368,63 @@ pub struct Disagreement {
     pub regex_category: String,
 }
 
+/// A collapsible source region produced by
+/// [`AstClassifier::folding_ranges`]. `start_line`/`end_line` are 0-based
+/// tree-sitter rows (not 1-based like [`ClassifyResult::line_num`]), since
+/// editors' folding-range APIs expect rows in that form directly.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FoldRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: String,
+}
+
+/// One entry in the tree returned by [`AstClassifier::outline`]: a
+/// declaration's name, its classification, its line span (0-based
+/// tree-sitter rows, matching [`FoldRange`]), and any declarations nested
+/// directly inside it (e.g. a class/impl's methods).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SymbolNode {
+    pub name: String,
+    pub category: String,
+    pub symbol: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub children: Vec<SymbolNode>,
+}
+
+/// Node kinds that count as outline entries. Deliberately narrower than
+/// [`AstClassifier::classify_node_kind`]'s `Declaration` category, which
+/// also covers local `let`/`var` bindings — those would flood a symbol
+/// outline with every local variable in every function body, so only
+/// module/class/function-shaped declarations (plus anonymous
+/// function/closure forms, named here explicitly so they can still surface
+/// under a synthetic name) are included.
+const OUTLINE_KINDS: &[&str] = &[
+    "function_definition",
+    "function_declaration",
+    "function_item",
+    "method_definition",
+    "class_definition",
+    "class_declaration",
+    "struct_item",
+    "struct_specifier",
+    "enum_item",
+    "enum_specifier",
+    "trait_item",
+    "interface_declaration",
+    "type_alias_declaration",
+    "type_item",
+    "const_item",
+    "static_item",
+    "impl_item",
+    "macro_definition",
+    "arrow_function",
+    "function_expression",
+    "lambda",
+    "func_literal",
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -497,4 +1513,275 @@ fn main() {
         // Falls back to regex
         assert_eq!(results.len(), 1);
     }
+
+    #[test]
+    fn test_incremental_reclassify_after_insert() {
+        let source = "import os\n\ndef main():\n    x = 42\n";
+        let mut doc =
+            IncrementalAstDocument::new(AstClassifier::new(), source, AstLanguage::Python)
+                .expect("source should parse");
+        assert_eq!(doc.results()[3].category, "assignment"); // x = 42
+
+        // Insert a `return x` line after `x = 42`.
+        let insert_at = source.len();
+        let new_source = format!("{source}    return x\n");
+        let edit = TextEdit {
+            start_byte: insert_at,
+            old_end_byte: insert_at,
+            new_end_byte: new_source.len(),
+            start_position: (4, 0),
+            old_end_position: (4, 0),
+            new_end_position: (5, 0),
+        };
+
+        let results = doc.reclassify(edit, &new_source);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[4].category, "modifier"); // return x
+    }
+
+    #[test]
+    fn test_incremental_reclassify_noop_edit_keeps_results() {
+        let source = "import os\ndef main():\n    pass\n";
+        let mut doc =
+            IncrementalAstDocument::new(AstClassifier::new(), source, AstLanguage::Python)
+                .expect("source should parse");
+        let before = doc.results().to_vec();
+
+        // An edit whose old and new ranges are both empty at the same
+        // position is a no-op; `changed_ranges` should report nothing.
+        let edit = TextEdit {
+            start_byte: 0,
+            old_end_byte: 0,
+            new_end_byte: 0,
+            start_position: (0, 0),
+            old_end_position: (0, 0),
+            new_end_position: (0, 0),
+        };
+        let after = doc.reclassify(edit, source);
+        assert_eq!(before.len(), after.len());
+        for (b, a) in before.iter().zip(after.iter()) {
+            assert_eq!(b.category, a.category);
+        }
+    }
+
+    #[test]
+    fn test_incremental_reclassify_reuses_cache_outside_edited_subtree() {
+        let source = "import os\n\ndef main():\n    x = 42\n";
+        let mut doc =
+            IncrementalAstDocument::new(AstClassifier::new(), source, AstLanguage::Python)
+                .expect("source should parse");
+        assert_eq!(doc.results()[0].category, "import"); // import os, untouched by the edit below
+
+        // Replace the trailing `2` in `x = 42` with `3`, entirely inside the
+        // function body — the import line is nowhere near the edited
+        // subtree, so its cached entry must come back unchanged rather than
+        // from a full re-walk.
+        let old_line = "    x = 42";
+        let new_source = source.replacen(old_line, "    x = 43", 1);
+        let edit_start = source.find(old_line).unwrap() + old_line.len() - 1;
+        let edit = TextEdit {
+            start_byte: edit_start,
+            old_end_byte: edit_start + 1,
+            new_end_byte: edit_start + 1,
+            start_position: (3, old_line.len() - 1),
+            old_end_position: (3, old_line.len()),
+            new_end_position: (3, old_line.len()),
+        };
+
+        let results = doc.reclassify(edit, &new_source);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].category, "import");
+        assert_eq!(results[0].symbol, "n");
+        assert_eq!(results[3].category, "assignment"); // x = 43
+    }
+
+    #[test]
+    fn test_classify_source_with_queries_builtin_python() {
+        let classifier = AstClassifier::new();
+        let source = r#"import os
+
+def main():
+    x = 42
+    if x > 0:
+        return x
+"#;
+        let results = classifier.classify_source_with_queries(source, AstLanguage::Python);
+        assert!(!results.is_empty());
+        assert_eq!(results[0].category, "import"); // import os
+        assert_eq!(results[0].symbol, "n");
+        assert_eq!(results[2].category, "declaration"); // def main
+        assert_eq!(results[3].category, "assignment"); // x = 42
+        assert_eq!(results[4].category, "logic"); // if x > 0
+    }
+
+    #[test]
+    fn test_classify_source_with_queries_custom_override() {
+        let scm = "(comment) @comment\n(function_definition) @declaration\n";
+        let classifier = AstClassifier::new()
+            .with_query_rules(AstLanguage::Python, scm)
+            .expect("query should compile");
+        let source = "# a comment\ndef f():\n    pass\n";
+        let results = classifier.classify_source_with_queries(source, AstLanguage::Python);
+        assert_eq!(results[0].category, "comment");
+        assert_eq!(results[1].category, "declaration");
+    }
+
+    #[test]
+    fn test_classify_source_with_queries_unrecognized_language_is_harmless() {
+        let classifier = AstClassifier::new();
+        let results = classifier.classify_source_with_queries("x = 1\n", AstLanguage::Go);
+        // No embedded query for Go yet: falls back to an empty query, so
+        // every line stays at the default Neutral/Zero classification
+        // rather than panicking.
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].category, "neutral");
+    }
+
+    #[test]
+    fn test_folding_ranges_covers_function_and_if_block() {
+        let classifier = AstClassifier::new();
+        let source = r#"def main():
+    x = 1
+    if x > 0:
+        return x
+"#;
+        let ranges = classifier.folding_ranges(source, AstLanguage::Python);
+        assert!(ranges
+            .iter()
+            .any(|r| r.kind == "declaration" && r.start_line == 0));
+        assert!(ranges.iter().any(|r| r.kind == "logic" && r.start_line == 2));
+    }
+
+    #[test]
+    fn test_folding_ranges_merges_consecutive_comment_lines() {
+        let classifier = AstClassifier::new();
+        let source = "# one\n# two\n# three\nx = 1\n";
+        let ranges = classifier.folding_ranges(source, AstLanguage::Python);
+        let comment_fold = ranges
+            .iter()
+            .find(|r| r.kind == "comment")
+            .expect("expected a merged comment fold");
+        assert_eq!(comment_fold.start_line, 0);
+        assert_eq!(comment_fold.end_line, 2);
+    }
+
+    #[test]
+    fn test_folding_ranges_skips_single_line_declarations() {
+        let classifier = AstClassifier::new();
+        let source = "def f(): pass\n";
+        let ranges = classifier.folding_ranges(source, AstLanguage::Python);
+        assert!(ranges.is_empty());
+    }
+
+    #[test]
+    fn test_outline_nests_methods_under_class() {
+        let classifier = AstClassifier::new();
+        let source = r#"class Greeter:
+    def hello(self):
+        return 1
+
+    def bye(self):
+        return 2
+
+CONST = 1
+"#;
+        let outline = classifier.outline(source, AstLanguage::Python);
+        let class_node = outline
+            .iter()
+            .find(|s| s.name == "Greeter")
+            .expect("expected a Greeter class symbol");
+        assert_eq!(class_node.category, "declaration");
+        assert_eq!(class_node.children.len(), 2);
+        assert!(class_node.children.iter().any(|c| c.name == "hello"));
+        assert!(class_node.children.iter().any(|c| c.name == "bye"));
+    }
+
+    #[test]
+    fn test_outline_unwraps_decorated_definition() {
+        let classifier = AstClassifier::new();
+        let source = "@staticmethod\ndef helper():\n    pass\n";
+        let outline = classifier.outline(source, AstLanguage::Python);
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].name, "helper");
+        // The wrapper's span (decorator included) should win, so the fold
+        // starts at the `@staticmethod` line, not the `def` line.
+        assert_eq!(outline[0].start_line, 0);
+    }
+
+    #[test]
+    fn test_outline_excludes_local_variable_declarations() {
+        let classifier = AstClassifier::new();
+        let source = "def f():\n    x = 1\n    return x\n";
+        let outline = classifier.outline(source, AstLanguage::Python);
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].name, "f");
+        assert!(outline[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_is_io_call_resolves_imported_module() {
+        let source = "import os\n\ndef main():\n    os.write(1, b'x')\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Python);
+        assert_eq!(results[3].category, "io"); // os.write(...)
+    }
+
+    #[test]
+    fn test_is_io_call_resolves_from_import_alias() {
+        let source = "from sys import stdout as out\n\ndef main():\n    out.write('x')\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Python);
+        assert_eq!(results[3].category, "io"); // out.write(...), out -> sys
+    }
+
+    #[test]
+    fn test_is_io_call_rejects_lookalike_user_function() {
+        let source = "def preprocess(x):\n    return x\n\ndef main():\n    preprocess(1)\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Python);
+        // "preprocess" contains "pre" but is a plain user function, not I/O.
+        assert_ne!(results[4].category, "io");
+    }
+
+    #[test]
+    fn test_is_io_call_rejects_unimported_lookalike_module() {
+        // `open_modal` is never imported from any I/O-bearing module, so it
+        // must not classify as I/O just because the substring "open" (or
+        // any I/O keyword) appears in its name.
+        let source = "def main():\n    open_modal()\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Python);
+        assert_ne!(results[1].category, "io");
+    }
+
+    #[test]
+    fn test_is_io_call_recognizes_python_builtin_print_and_open() {
+        let source = "def main():\n    print('hi')\n    open('f.txt')\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Python);
+        assert_eq!(results[1].category, "io");
+        assert_eq!(results[2].category, "io");
+    }
+
+    #[test]
+    fn test_is_io_call_resolves_rust_use_declaration() {
+        let source = "use std::io::Write;\n\nfn main() {\n    stdout().write(b\"x\").unwrap();\n}\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Rust);
+        // Only the `Write` trait is imported, not `stdout`, so the call line
+        // itself does not resolve to I/O. Check the import line, and that
+        // the `use` binds `Write` to a module `is_io_call` accepts.
+        assert_eq!(results[0].category, "import");
+        let mut bindings = HashMap::new();
+        AstClassifier::collect_import_bindings("use_declaration", "use std::io::Write;", AstLanguage::Rust, &mut bindings);
+        assert!(AstClassifier::is_io_call("Write", &bindings, AstLanguage::Rust));
+    }
+
+    #[test]
+    fn test_is_io_call_resolves_go_import_spec() {
+        let source = "package main\n\nimport \"fmt\"\n\nfunc main() {\n\tfmt.Println(\"hi\")\n}\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Go);
+        assert_eq!(results[5].category, "io"); // fmt.Println(...)
+    }
+
+    #[test]
+    fn test_is_io_call_resolves_c_stdio_include() {
+        let source = "#include <stdio.h>\n\nint main() {\n    printf(\"hi\");\n    return 0;\n}\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::C);
+        assert_eq!(results[3].category, "io"); // printf("hi");
+    }
 }
@@ -0,0 +1,8 @@
+//! `uvspeed-lsp` — stdio entry point for the quantum-prefix language
+//! server. Editors spawn this binary directly (the same way they spawn
+//! `rust-analyzer`); all the protocol handling lives in
+//! [`uvspeed_prefix_engine::lsp`].
+
+fn main() -> std::io::Result<()> {
+    uvspeed_prefix_engine::lsp::run()
+}
@@ -0,0 +1,585 @@
+//! Minimal Language Server exposing the quantum-prefix classifier as LSP
+//! `textDocument/semanticTokens/full` (and its `/delta` companion), so
+//! editors can render the same gutter the crate's CLI prints, natively, as
+//! the user types.
+//!
+//! Speaks JSON-RPC 2.0 over stdio using `Content-Length`-framed messages
+//! (the LSP base protocol). The crate already depends on
+//! `serde`/`serde_json` for its result types, so messages are handled as
+//! loosely-typed [`serde_json::Value`] rather than pulling in a dedicated
+//! `lsp-types`/`tower-lsp` dependency just for framing a handful of methods.
+//!
+//! Known simplification: LSP positions are UTF-16 code unit offsets per the
+//! spec; this server treats `character` as a byte offset into the line
+//! instead. That only diverges from the spec on non-ASCII lines, and
+//! fixing it properly needs a UTF-16-aware line index this crate doesn't
+//! otherwise maintain.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use serde_json::{json, Value};
+
+use crate::ast::{AstClassifier, AstLanguage, IncrementalAstDocument, TextEdit};
+use crate::ClassifyResult;
+
+/// Semantic token types, in legend order. Index into this slice is the
+/// `tokenType` every emitted token references; editors resolve it back to
+/// a color via the `semanticTokensProvider.legend` advertised in
+/// `initialize`. Kept in the same order as [`crate::Category::ALL`] so the
+/// legend index and [`crate::Category::index`] agree, with `"unknown"`
+/// last as the catch-all.
+const TOKEN_TYPES: &[&str] = &[
+    "declaration",
+    "logic",
+    "io",
+    "assignment",
+    "neutral",
+    "comment",
+    "modifier",
+    "import",
+    "unknown",
+];
+
+fn token_type_index(category: &str) -> u32 {
+    TOKEN_TYPES
+        .iter()
+        .position(|t| *t == category)
+        .unwrap_or(TOKEN_TYPES.len() - 1) as u32
+}
+
+/// Server-side state for one open document.
+struct Document {
+    text: String,
+    language_id: String,
+    lang: Option<AstLanguage>,
+    /// Present only when `lang` is AST-supported; carries the live
+    /// tree-sitter tree forward across edits so `didChange` can reparse
+    /// incrementally instead of from scratch.
+    incremental: Option<IncrementalAstDocument>,
+    results: Vec<ClassifyResult>,
+    /// The `(resultId, data)` last handed to the client by either
+    /// `semanticTokens/full` or `semanticTokens/full/delta`, so the next
+    /// delta request has a baseline to diff against.
+    last_tokens: Option<(String, Vec<u32>)>,
+    /// Monotonic counter minted into each new `resultId`.
+    next_result_id: u64,
+}
+
+impl Document {
+    fn open(text: String, language_id: String) -> Self {
+        let results = AstClassifier::new().classify_auto(&text, &language_id);
+        let lang = AstLanguage::from_str(&language_id);
+        let incremental = lang.and_then(|l| IncrementalAstDocument::new(AstClassifier::new(), &text, l));
+        Self {
+            text,
+            language_id,
+            lang,
+            incremental,
+            results,
+            last_tokens: None,
+            next_result_id: 0,
+        }
+    }
+
+    /// Replace the whole document (used for full-sync `didChange` events
+    /// and as the fallback when an incremental edit can't be applied).
+    fn replace_all(&mut self, text: String) {
+        self.results = AstClassifier::new().classify_auto(&text, &self.language_id);
+        self.incremental = self
+            .lang
+            .and_then(|l| IncrementalAstDocument::new(AstClassifier::new(), &text, l));
+        self.text = text;
+    }
+
+    /// Apply one incremental `range` edit, reparsing only the affected
+    /// subtree when an incremental document is available.
+    fn apply_edit(&mut self, edit: TextEdit, new_text: String) {
+        match self.incremental.as_mut() {
+            Some(incremental) => {
+                self.results = incremental.reclassify(edit, &new_text).to_vec();
+            }
+            None => {
+                self.results = AstClassifier::new().classify_auto(&new_text, &self.language_id);
+            }
+        }
+        self.text = new_text;
+    }
+}
+
+/// Run the server, reading requests from `stdin` and writing responses to
+/// `stdout` until `exit` is received or the client closes the pipe.
+pub fn run() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut reader = BufReader::new(stdin.lock());
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+    let mut documents: HashMap<String, Document> = HashMap::new();
+
+    while let Some(msg) = read_message(&mut reader)? {
+        let method = msg.get("method").and_then(Value::as_str).unwrap_or("");
+        let id = msg.get("id").cloned();
+
+        match method {
+            "initialize" => respond(&mut writer, id, initialize_result())?,
+            "initialized" | "$/cancelRequest" => {}
+            "textDocument/didOpen" => handle_did_open(&mut documents, &msg),
+            "textDocument/didChange" => handle_did_change(&mut documents, &msg),
+            "textDocument/didClose" => handle_did_close(&mut documents, &msg),
+            "textDocument/semanticTokens/full" => {
+                let result = handle_semantic_tokens(&mut documents, &msg);
+                respond(&mut writer, id, result)?;
+            }
+            "textDocument/semanticTokens/full/delta" => {
+                let result = handle_semantic_tokens_delta(&mut documents, &msg);
+                respond(&mut writer, id, result)?;
+            }
+            "shutdown" => respond(&mut writer, id, Value::Null)?,
+            "exit" => break,
+            _ => {
+                if let Some(id) = id {
+                    write_message(
+                        &mut writer,
+                        &json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": -32601, "message": format!("method not found: {method}") }
+                        }),
+                    )?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn respond<W: Write>(writer: &mut W, id: Option<Value>, result: Value) -> io::Result<()> {
+    if let Some(id) = id {
+        write_message(writer, &json!({ "jsonrpc": "2.0", "id": id, "result": result }))?;
+    }
+    Ok(())
+}
+
+fn initialize_result() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": {
+                "openClose": true,
+                "change": 2, // Incremental
+            },
+            "semanticTokensProvider": {
+                "legend": {
+                    "tokenTypes": TOKEN_TYPES,
+                    "tokenModifiers": Vec::<&str>::new(),
+                },
+                "full": { "delta": true },
+            },
+        },
+        "serverInfo": { "name": "uvspeed-lsp" },
+    })
+}
+
+fn handle_did_open(documents: &mut HashMap<String, Document>, msg: &Value) {
+    let Some(td) = msg.get("params").and_then(|p| p.get("textDocument")) else {
+        return;
+    };
+    let (Some(uri), Some(text), Some(language_id)) = (
+        td.get("uri").and_then(Value::as_str),
+        td.get("text").and_then(Value::as_str),
+        td.get("languageId").and_then(Value::as_str),
+    ) else {
+        return;
+    };
+    documents.insert(
+        uri.to_string(),
+        Document::open(text.to_string(), language_id.to_string()),
+    );
+}
+
+fn handle_did_close(documents: &mut HashMap<String, Document>, msg: &Value) {
+    if let Some(uri) = msg
+        .get("params")
+        .and_then(|p| p.get("textDocument"))
+        .and_then(|td| td.get("uri"))
+        .and_then(Value::as_str)
+    {
+        documents.remove(uri);
+    }
+}
+
+fn handle_did_change(documents: &mut HashMap<String, Document>, msg: &Value) {
+    let Some(params) = msg.get("params") else {
+        return;
+    };
+    let Some(uri) = params
+        .get("textDocument")
+        .and_then(|td| td.get("uri"))
+        .and_then(Value::as_str)
+    else {
+        return;
+    };
+    let Some(changes) = params.get("contentChanges").and_then(Value::as_array) else {
+        return;
+    };
+    let Some(doc) = documents.get_mut(uri) else {
+        return;
+    };
+
+    for change in changes {
+        let Some(new_text) = change.get("text").and_then(Value::as_str) else {
+            continue;
+        };
+
+        match change.get("range") {
+            Some(range) => apply_range_change(doc, range, new_text),
+            // No range: this change event replaces the whole document.
+            None => doc.replace_all(new_text.to_string()),
+        }
+    }
+}
+
+fn apply_range_change(doc: &mut Document, range: &Value, new_text: &str) {
+    let (start_line, start_char) = lsp_position(range, "start");
+    let (end_line, end_char) = lsp_position(range, "end");
+    let start_byte = position_to_byte_offset(&doc.text, start_line, start_char);
+    let old_end_byte = position_to_byte_offset(&doc.text, end_line, end_char);
+
+    let mut new_full = String::with_capacity(doc.text.len() + new_text.len());
+    new_full.push_str(&doc.text[..start_byte]);
+    new_full.push_str(new_text);
+    new_full.push_str(&doc.text[old_end_byte..]);
+
+    let new_end_position = position_after_insert(start_line, start_char, new_text);
+    let edit = TextEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte: start_byte + new_text.len(),
+        start_position: (start_line, start_char),
+        old_end_position: (end_line, end_char),
+        new_end_position,
+    };
+    doc.apply_edit(edit, new_full);
+}
+
+fn lsp_position(range: &Value, key: &str) -> (usize, usize) {
+    let pos = range.get(key);
+    let line = pos
+        .and_then(|p| p.get("line"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as usize;
+    let character = pos
+        .and_then(|p| p.get("character"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as usize;
+    (line, character)
+}
+
+fn position_to_byte_offset(text: &str, line: usize, character: usize) -> usize {
+    let mut offset = 0;
+    for (i, l) in text.split('\n').enumerate() {
+        if i == line {
+            return offset + character.min(l.len());
+        }
+        offset += l.len() + 1; // +1 for the '\n' this split consumed
+    }
+    text.len()
+}
+
+fn position_after_insert(start_line: usize, start_char: usize, inserted: &str) -> (usize, usize) {
+    let newline_count = inserted.matches('\n').count();
+    if newline_count == 0 {
+        (start_line, start_char + inserted.len())
+    } else {
+        let last_line_len = inserted.rsplit('\n').next().unwrap_or("").len();
+        (start_line + newline_count, last_line_len)
+    }
+}
+
+fn handle_semantic_tokens(documents: &mut HashMap<String, Document>, msg: &Value) -> Value {
+    let uri = msg
+        .get("params")
+        .and_then(|p| p.get("textDocument"))
+        .and_then(|td| td.get("uri"))
+        .and_then(Value::as_str)
+        .unwrap_or("");
+
+    match documents.get_mut(uri) {
+        Some(doc) => {
+            let data = encode_semantic_tokens(&doc.results, &doc.text);
+            let result_id = doc.next_result_id.to_string();
+            doc.next_result_id += 1;
+            doc.last_tokens = Some((result_id.clone(), data.clone()));
+            json!({ "resultId": result_id, "data": data })
+        }
+        None => json!({ "data": Vec::<u32>::new() }),
+    }
+}
+
+/// Handle `textDocument/semanticTokens/full/delta`: diff the freshly
+/// encoded tokens against whatever `resultId` the client last saw. Falls
+/// back to a full `data` response (no `edits`) whenever there's nothing to
+/// diff against, per the spec's "server doesn't remember that baseline"
+/// allowance.
+fn handle_semantic_tokens_delta(documents: &mut HashMap<String, Document>, msg: &Value) -> Value {
+    let params = msg.get("params");
+    let uri = params
+        .and_then(|p| p.get("textDocument"))
+        .and_then(|td| td.get("uri"))
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    let previous_result_id = params
+        .and_then(|p| p.get("previousResultId"))
+        .and_then(Value::as_str);
+
+    let Some(doc) = documents.get_mut(uri) else {
+        return json!({ "data": Vec::<u32>::new() });
+    };
+
+    let new_data = encode_semantic_tokens(&doc.results, &doc.text);
+    let result_id = doc.next_result_id.to_string();
+    doc.next_result_id += 1;
+
+    let response = match (previous_result_id, &doc.last_tokens) {
+        (Some(previous), Some((baseline_id, old_data))) if previous == baseline_id => {
+            let edit = diff_tokens(old_data, &new_data);
+            json!({ "resultId": result_id, "edits": [edit] })
+        }
+        _ => json!({ "resultId": result_id, "data": new_data.clone() }),
+    };
+
+    doc.last_tokens = Some((result_id, new_data));
+    response
+}
+
+/// Diff two flat semantic-token arrays down to a single LSP edit: the
+/// longest common prefix and suffix are trimmed off, and everything left in
+/// between is replaced wholesale. `edits` don't need to align to the 5-wide
+/// token-tuple boundaries — the client applies them as a plain array
+/// splice, same as a text edit over an array instead of a string.
+fn diff_tokens(old: &[u32], new: &[u32]) -> Value {
+    let prefix_len = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_rest = &old[prefix_len..];
+    let new_rest = &new[prefix_len..];
+
+    let suffix_len = old_rest
+        .iter()
+        .rev()
+        .zip(new_rest.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(old_rest.len())
+        .min(new_rest.len());
+
+    let delete_count = old_rest.len() - suffix_len;
+    let data = &new_rest[..new_rest.len() - suffix_len];
+
+    json!({
+        "start": prefix_len,
+        "deleteCount": delete_count,
+        "data": data,
+    })
+}
+
+/// Encode one token per non-empty classified line in the LSP relative
+/// format: `[deltaLine, deltaStart, length, tokenType, tokenModifiers]`
+/// repeated per token. Every token starts at column 0 of its line, so
+/// `deltaStart` is always 0 — correct whether or not the previous token was
+/// on the same line, since this server never emits two tokens per line.
+fn encode_semantic_tokens(results: &[ClassifyResult], source: &str) -> Vec<u32> {
+    let lines: Vec<&str> = source.split('\n').collect();
+    let mut data = Vec::with_capacity(results.len() * 5);
+    let mut prev_line: u32 = 0;
+
+    for result in results {
+        let line_idx = (result.line_num - 1) as u32;
+        let length = lines
+            .get(line_idx as usize)
+            .map(|l| l.len() as u32)
+            .unwrap_or(0);
+        if length == 0 {
+            continue;
+        }
+        let delta_line = line_idx - prev_line;
+        data.extend_from_slice(&[
+            delta_line,
+            0,
+            length,
+            token_type_index(&result.category),
+            0,
+        ]);
+        prev_line = line_idx;
+    }
+
+    data
+}
+
+fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(rest) = line.strip_prefix("Content-Length:") {
+            content_length = rest.trim().parse().ok();
+        }
+    }
+    let Some(len) = content_length else {
+        return Ok(None);
+    };
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    serde_json::from_slice(&buf)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message<W: Write>(writer: &mut W, value: &Value) -> io::Result<()> {
+    let body = serde_json::to_vec(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_type_index_known_and_unknown_categories() {
+        assert_eq!(token_type_index("declaration"), 0);
+        assert_eq!(token_type_index("import"), 7);
+        assert_eq!(token_type_index("something-else"), TOKEN_TYPES.len() as u32 - 1);
+    }
+
+    #[test]
+    fn test_position_to_byte_offset_finds_line_start() {
+        let text = "abc\ndef\nghi";
+        assert_eq!(position_to_byte_offset(text, 0, 0), 0);
+        assert_eq!(position_to_byte_offset(text, 1, 0), 4);
+        assert_eq!(position_to_byte_offset(text, 2, 2), 10);
+    }
+
+    #[test]
+    fn test_position_after_insert_single_and_multi_line() {
+        assert_eq!(position_after_insert(2, 3, "xy"), (2, 5));
+        assert_eq!(position_after_insert(2, 3, "a\nbcd"), (3, 3));
+    }
+
+    #[test]
+    fn test_encode_semantic_tokens_skips_empty_lines() {
+        let source = "import os\n\nx = 1\n";
+        let results = AstClassifier::new().classify_source(source, AstLanguage::Python);
+        let data = encode_semantic_tokens(&results, source);
+        // 3 lines total, but the blank line 2 contributes no token, so we
+        // expect two 5-field token records.
+        assert_eq!(data.len(), 10);
+        assert_eq!(data[0], 0); // first token: deltaLine 0 (line 0)
+        assert_eq!(data[5], 2); // second token: deltaLine 2 (line 0 -> line 2)
+    }
+
+    #[test]
+    fn test_document_open_and_incremental_edit_reclassifies() {
+        let mut doc = Document::open("x = 1\n".to_string(), "python".to_string());
+        assert_eq!(doc.results[0].category, "assignment");
+
+        let edit = TextEdit {
+            start_byte: 0,
+            old_end_byte: 0,
+            new_end_byte: 4,
+            start_position: (0, 0),
+            old_end_position: (0, 0),
+            new_end_position: (0, 4),
+        };
+        doc.apply_edit(edit, "def f():\n    x = 1\n".to_string());
+        assert_eq!(doc.results[0].category, "declaration");
+    }
+
+    #[test]
+    fn test_diff_tokens_trims_common_prefix_and_suffix() {
+        let old = vec![0, 0, 5, 0, 0, /**/ 2, 0, 1, 3, 0, /**/ 1, 0, 5, 0, 0];
+        let new = vec![0, 0, 5, 0, 0, /**/ 2, 0, 1, 7, 0, /**/ 1, 0, 5, 0, 0];
+        let edit = diff_tokens(&old, &new);
+        assert_eq!(edit["start"], 8);
+        assert_eq!(edit["deleteCount"], 1);
+        assert_eq!(edit["data"], json!([7]));
+    }
+
+    #[test]
+    fn test_diff_tokens_identical_arrays_produce_empty_edit() {
+        let tokens = vec![0, 0, 5, 0, 0];
+        let edit = diff_tokens(&tokens, &tokens);
+        assert_eq!(edit["start"], tokens.len());
+        assert_eq!(edit["deleteCount"], 0);
+        assert_eq!(edit["data"], json!([]));
+    }
+
+    #[test]
+    fn test_semantic_tokens_delta_matches_known_baseline() {
+        let mut documents = HashMap::new();
+        documents.insert(
+            "file:///a.py".to_string(),
+            Document::open("x = 1\n".to_string(), "python".to_string()),
+        );
+
+        let full = handle_semantic_tokens(
+            &mut documents,
+            &json!({ "params": { "textDocument": { "uri": "file:///a.py" } } }),
+        );
+        let first_result_id = full["resultId"].as_str().unwrap().to_string();
+
+        let doc = documents.get_mut("file:///a.py").unwrap();
+        doc.apply_edit(
+            TextEdit {
+                start_byte: 0,
+                old_end_byte: 0,
+                new_end_byte: 4,
+                start_position: (0, 0),
+                old_end_position: (0, 0),
+                new_end_position: (0, 4),
+            },
+            "def f():\n    x = 1\n".to_string(),
+        );
+
+        let delta = handle_semantic_tokens_delta(
+            &mut documents,
+            &json!({
+                "params": {
+                    "textDocument": { "uri": "file:///a.py" },
+                    "previousResultId": first_result_id,
+                }
+            }),
+        );
+        assert!(delta.get("edits").is_some(), "expected an edits response, got {delta:?}");
+        assert!(delta.get("data").is_none());
+    }
+
+    #[test]
+    fn test_semantic_tokens_delta_falls_back_to_full_on_unknown_baseline() {
+        let mut documents = HashMap::new();
+        documents.insert(
+            "file:///a.py".to_string(),
+            Document::open("x = 1\n".to_string(), "python".to_string()),
+        );
+
+        let delta = handle_semantic_tokens_delta(
+            &mut documents,
+            &json!({
+                "params": {
+                    "textDocument": { "uri": "file:///a.py" },
+                    "previousResultId": "stale-id-the-server-never-issued",
+                }
+            }),
+        );
+        assert!(delta.get("data").is_some(), "expected a full-data response, got {delta:?}");
+        assert!(delta.get("edits").is_none());
+    }
+}
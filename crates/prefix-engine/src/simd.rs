@@ -15,12 +15,18 @@
 // compiler can still optimize for SIMD.
 
 use crate::{Category, ClassifyResult, PrefixSymbol};
+use std::collections::VecDeque;
 
 /// SIMD-capable prefix classifier.
 /// Uses batch-oriented processing to maximize throughput.
 pub struct SimdClassifier {
     /// Classification lookup table (256 entries for first-byte dispatch)
     first_byte_table: [u8; 256],
+    /// Compiled multi-pattern automaton for I/O keyword/substring detection.
+    io_matcher: AhoCorasick,
+    /// User-extensible keyword policy, compiled into a trie for anchored
+    /// start-of-line matching.
+    keywords: CompiledKeywords,
 }
 
 impl Default for SimdClassifier {
@@ -30,20 +36,33 @@ impl Default for SimdClassifier {
 }
 
 impl SimdClassifier {
+    /// Build a classifier using the built-in keyword table (today's
+    /// hardcoded Rust/Python/JS/C-family keyword set).
     pub fn new() -> Self {
-        let mut table = [0xFFu8; 256]; // 0xFF = no match, continue to deeper analysis
+        Self::with_table(KeywordTable::builtin())
+    }
+
+    /// Build a classifier from a caller-supplied [`KeywordTable`], separating
+    /// the classification policy (keywords → symbol/category) from the
+    /// scanning engine. Lets callers register keywords for languages the
+    /// built-in table doesn't cover (Go, Ruby, SQL, ...) without touching
+    /// this module.
+    pub fn with_table(table: KeywordTable) -> Self {
+        let mut byte_table = [0xFFu8; 256]; // 0xFF = no match, continue to deeper analysis
 
         // Comment first-bytes → MinusZero (5)
-        table[b'#' as usize] = 5; // Python/Shell comments
-        table[b';' as usize] = 5; // Assembly/Lisp comments
+        byte_table[b'#' as usize] = 5; // Python/Shell comments
+        byte_table[b';' as usize] = 5; // Assembly/Lisp comments
 
         // First-byte hints (need secondary check)
-        table[b'/' as usize] = 0xFE; // Could be // or /* comment
-        table[b'-' as usize] = 0xFD; // Could be -- comment
-        table[b'<' as usize] = 0xFC; // Could be <!-- comment
+        byte_table[b'/' as usize] = 0xFE; // Could be // or /* comment
+        byte_table[b'-' as usize] = 0xFD; // Could be -- comment
+        byte_table[b'<' as usize] = 0xFC; // Could be <!-- comment
 
         Self {
-            first_byte_table: table,
+            first_byte_table: byte_table,
+            io_matcher: AhoCorasick::build(&IO_PATTERNS),
+            keywords: table.compile(),
         }
     }
 
@@ -74,7 +93,12 @@ impl SimdClassifier {
     /// Fast single-line classification using the first-byte dispatch table.
     #[inline(always)]
     fn classify_line_fast(&self, line: &str) -> (PrefixSymbol, Category) {
-        let trimmed = line.trim();
+        // Skip the common run of leading ASCII spaces/tabs with the
+        // vectorized scan before falling through to `str::trim`, which
+        // still runs to catch trailing whitespace and any non-ASCII
+        // whitespace the fast path doesn't special-case.
+        let lead = count_leading_whitespace(line.as_bytes());
+        let trimmed = line[lead..].trim();
         if trimmed.is_empty() {
             return (PrefixSymbol::Zero, Category::Neutral);
         }
@@ -112,81 +136,23 @@ impl SimdClassifier {
         }
     }
 
-    /// Deep classification — keyword matching with branch-free patterns.
+    /// Deep classification — keyword matching against the compiled,
+    /// user-extensible [`KeywordTable`].
     /// This is the hot path that benefits most from compiler auto-vectorization.
     #[inline]
     fn classify_deep(&self, trimmed: &str) -> (PrefixSymbol, Category) {
         let bytes = trimmed.as_bytes();
-        let len = bytes.len();
-
-        // Keyword matching via prefix comparison
-        // Import keywords: import, from, use, require, #include, using, extern, mod, package
-        if (len >= 6 && &bytes[..6] == b"import" && (len == 6 || is_boundary(bytes[6])))
-            || (len >= 4 && &bytes[..4] == b"from" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 3 && &bytes[..3] == b"use" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 7 && &bytes[..7] == b"require" && (len == 7 || is_boundary(bytes[7])))
-            || (len >= 8 && &bytes[..8] == b"#include")
-            || (len >= 5 && &bytes[..5] == b"using" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 6 && &bytes[..6] == b"extern" && (len == 6 || is_boundary(bytes[6])))
-            || (len >= 3 && &bytes[..3] == b"mod" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 7 && &bytes[..7] == b"package" && (len == 7 || is_boundary(bytes[7])))
-        {
-            return (PrefixSymbol::N, Category::Import);
-        }
-
-        // Declaration keywords
-        if (len >= 2 && &bytes[..2] == b"fn" && (len == 2 || is_boundary(bytes[2])))
-            || (len >= 8 && &bytes[..8] == b"function" && (len == 8 || is_boundary(bytes[8])))
-            || (len >= 3 && &bytes[..3] == b"def" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 5 && &bytes[..5] == b"class" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 6 && &bytes[..6] == b"struct" && (len == 6 || is_boundary(bytes[6])))
-            || (len >= 4 && &bytes[..4] == b"enum" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 5 && &bytes[..5] == b"trait" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 5 && &bytes[..5] == b"const" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 3 && &bytes[..3] == b"let" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 3 && &bytes[..3] == b"var" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 4 && &bytes[..4] == b"type" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 6 && &bytes[..6] == b"static" && (len == 6 || is_boundary(bytes[6])))
-            || (len >= 4 && &bytes[..4] == b"impl" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 6 && &bytes[..6] == b"pub fn")
-            || (len >= 10 && &bytes[..10] == b"pub struct")
-            || (len >= 8 && &bytes[..8] == b"pub enum")
-            || (len >= 8 && &bytes[..8] == b"async fn")
-            || (len >= 6 && &bytes[..6] == b"export" && (len == 6 || is_boundary(bytes[6])))
-        {
-            return (PrefixSymbol::PlusOne, Category::Declaration);
-        }
-
-        // Logic keywords
-        if (len >= 2 && &bytes[..2] == b"if" && (len == 2 || is_boundary(bytes[2])))
-            || (len >= 4 && &bytes[..4] == b"else" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 4 && &bytes[..4] == b"elif" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 3 && &bytes[..3] == b"for" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 5 && &bytes[..5] == b"while" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 4 && &bytes[..4] == b"loop" && (len == 4 || is_boundary(bytes[4])))
-            || (len >= 5 && &bytes[..5] == b"match" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 6 && &bytes[..6] == b"switch" && (len == 6 || is_boundary(bytes[6])))
-            || (len >= 3 && &bytes[..3] == b"try" && (len == 3 || is_boundary(bytes[3])))
-            || (len >= 5 && &bytes[..5] == b"catch" && (len == 5 || is_boundary(bytes[5])))
-            || trimmed.starts_with("} else")
-        {
-            return (PrefixSymbol::One, Category::Logic);
-        }
 
-        // Modifier keywords
-        if (len >= 6 && &bytes[..6] == b"return" && (len == 6 || is_boundary(bytes[6])))
-            || (len >= 5 && &bytes[..5] == b"yield" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 5 && &bytes[..5] == b"break" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 8 && &bytes[..8] == b"continue" && (len == 8 || is_boundary(bytes[8])))
-            || (len >= 5 && &bytes[..5] == b"throw" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 5 && &bytes[..5] == b"raise" && (len == 5 || is_boundary(bytes[5])))
-            || (len >= 5 && &bytes[..5] == b"defer" && (len == 5 || is_boundary(bytes[5])))
-        {
-            return (PrefixSymbol::PlusN, Category::Modifier);
+        // Keyword matching — anchored trie walk over the compiled table,
+        // replacing the old per-category if-ladders. Policy (which keywords
+        // map to which symbol/category) now lives in data, not this branch.
+        if let Some((symbol, category)) = self.keywords.match_at_start(bytes) {
+            return (symbol, category);
         }
 
-        // I/O patterns (substring search — this is where SIMD shines)
-        if contains_any_simd_friendly(bytes, &IO_PATTERNS) {
+        // I/O patterns — single-pass Aho-Corasick scan instead of an O(n*m)
+        // substring search per pattern.
+        if self.io_matcher.is_match(bytes) {
             return (PrefixSymbol::MinusOne, Category::IO);
         }
 
@@ -242,6 +208,586 @@ impl SimdClassifier {
         }
         packed
     }
+
+    /// Prescan a whole, unsplit source buffer for comment-sentinel bytes
+    /// (`#`, `/`, `-`, `<`, `;`) that sit at the very start of a line,
+    /// using the vectorized path when available. Returns the byte offset
+    /// of each candidate line start, which a batch caller can use to route
+    /// straight into the comment check instead of repeating first-byte
+    /// dispatch over the whole buffer.
+    pub fn scan_comment_candidates(&self, source: &str) -> Vec<usize> {
+        let bytes = source.as_bytes();
+        let mut candidates = Vec::new();
+        let mut offset = 0usize;
+        while offset < bytes.len() {
+            match find_comment_sentinel(&bytes[offset..]) {
+                Some(rel) => {
+                    let pos = offset + rel;
+                    if pos == 0 || bytes[pos - 1] == b'\n' {
+                        candidates.push(pos);
+                    }
+                    offset = pos + 1;
+                }
+                None => break,
+            }
+        }
+        candidates
+    }
+
+    /// Classify `reader` block-by-block instead of requiring the whole
+    /// source to be buffered up front, so memory stays O(block size)
+    /// regardless of input size as long as every line fits in one block;
+    /// a longer line doubles the buffer until it fits, and the buffer does
+    /// not shrink afterwards. Lines are discovered at `\n` boundaries
+    /// within each block; a trailing partial line is carried over and
+    /// prefixed onto the next read.
+    pub fn classify_stream<R: std::io::Read>(&self, reader: R) -> StreamClassifier<'_, R> {
+        StreamClassifier {
+            classifier: self,
+            reader,
+            buf: vec![0u8; STREAM_BLOCK_SIZE],
+            start: 0,
+            filled: 0,
+            line_num: 0,
+            eof: false,
+        }
+    }
+
+    /// Convenience wrapper around [`classify_stream`](Self::classify_stream)
+    /// that opens `path` for reading and classifies it line-by-line without
+    /// materializing the whole file.
+    pub fn classify_path(
+        &self,
+        path: &std::path::Path,
+    ) -> std::io::Result<StreamClassifier<'_, std::fs::File>> {
+        let file = std::fs::File::options().read(true).open(path)?;
+        Ok(self.classify_stream(file))
+    }
+
+    /// Like [`classify_path`](Self::classify_path), but memory-maps the file
+    /// instead of streaming it in blocks. Worthwhile when the file is
+    /// already page-cached and a single contiguous view is cheaper than
+    /// repeated buffered reads. Only compiled with the `mmap` feature;
+    /// without it this method does not exist. The file must not be
+    /// truncated or modified while the call runs.
+    #[cfg(feature = "mmap")]
+    pub fn classify_path_mmap(&self, path: &std::path::Path) -> std::io::Result<Vec<ClassifyResult>> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: caller guarantees `path` is not concurrently truncated or
+        // modified for the lifetime of the mapping, per `Mmap::map`'s
+        // contract.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        let source = String::from_utf8_lossy(&mmap);
+        let lines: Vec<&str> = source.lines().collect();
+        Ok(self.classify_batch(&lines))
+    }
+}
+
+/// Default block size for [`SimdClassifier::classify_stream`]: large enough
+/// to amortize syscall overhead, small enough to keep memory bounded.
+const STREAM_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Iterator returned by [`SimdClassifier::classify_stream`]/
+/// [`SimdClassifier::classify_path`]. Yields one [`ClassifyResult`] per line
+/// as blocks are read, without ever holding the whole input in memory.
+pub struct StreamClassifier<'c, R> {
+    classifier: &'c SimdClassifier,
+    reader: R,
+    buf: Vec<u8>,
+    /// Start of the unconsumed region within `buf`.
+    start: usize,
+    /// `buf[..filled]` holds valid bytes read so far.
+    filled: usize,
+    line_num: usize,
+    eof: bool,
+}
+
+impl<'c, R: std::io::Read> StreamClassifier<'c, R> {
+    /// Classify `buf[start..end]` as one line. Takes a byte range rather
+    /// than a borrowed slice so the borrow of `self.buf` ends before
+    /// `self.line_num` is bumped.
+    fn classify_line_range(&mut self, start: usize, end: usize) -> ClassifyResult {
+        let (symbol, category) = {
+            let line = String::from_utf8_lossy(&self.buf[start..end]);
+            self.classifier.classify_line_fast(&line)
+        };
+        self.line_num += 1;
+        ClassifyResult {
+            symbol: symbol.as_str().to_string(),
+            category: category.as_str().to_string(),
+            bits: symbol.to_bits(),
+            coords: symbol.to_3d(),
+            line_num: self.line_num,
+        }
+    }
+}
+
+impl<'c, R: std::io::Read> Iterator for StreamClassifier<'c, R> {
+    type Item = std::io::Result<ClassifyResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(rel_nl) = self.buf[self.start..self.filled]
+                .iter()
+                .position(|&b| b == b'\n')
+            {
+                let line_end = self.start + rel_nl;
+                let start = self.start;
+                self.start = line_end + 1;
+                return Some(Ok(self.classify_line_range(start, line_end)));
+            }
+
+            if self.eof {
+                if self.start < self.filled {
+                    let start = self.start;
+                    let filled = self.filled;
+                    self.start = filled;
+                    return Some(Ok(self.classify_line_range(start, filled)));
+                }
+                return None;
+            }
+
+            // Carry the unconsumed partial line to the front of the buffer,
+            // then read the next block in after it.
+            self.buf.copy_within(self.start..self.filled, 0);
+            self.filled -= self.start;
+            self.start = 0;
+
+            if self.filled == self.buf.len() {
+                // A single line outgrew the block size; double the buffer
+                // rather than reporting a truncated line.
+                self.buf.resize(self.buf.len() * 2, 0);
+            }
+
+            match self.reader.read(&mut self.buf[self.filled..]) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.filled += n,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+// ── Vectorized byte scanning ──
+//
+// A lightweight byte cursor over raw pointers, plus an AVX2/NEON fast path
+// (with a scalar fallback) for the two hot scans that benefit most from
+// wide comparisons: finding comment-sentinel bytes and skipping leading
+// whitespace. Dispatch happens at runtime via `is_x86_feature_detected!` so
+// a single binary still uses AVX2 when the host supports it.
+
+/// Raw-pointer byte cursor mirroring the zero-bounds-check pattern of
+/// `std::slice::Iter`: advancing is a pointer bump, and `peek`/`peek_n` do a
+/// single length check up front rather than re-validating every read.
+struct ByteCursor<'a> {
+    start: *const u8,
+    end: *const u8,
+    cursor: *const u8,
+    _marker: std::marker::PhantomData<&'a [u8]>,
+}
+
+impl<'a> ByteCursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        let start = bytes.as_ptr();
+        // SAFETY: `start` and `bytes.len()` come from the same slice, so the
+        // one-past-the-end pointer is always valid to form.
+        let end = unsafe { start.add(bytes.len()) };
+        Self {
+            start,
+            end,
+            cursor: start,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    #[inline]
+    #[allow(dead_code)]
+    fn position(&self) -> usize {
+        // SAFETY: `cursor` only ever advances within `[start, end]`.
+        unsafe { self.cursor.offset_from(self.start) as usize }
+    }
+
+    #[inline]
+    fn remaining(&self) -> usize {
+        // SAFETY: `cursor` only ever advances within `[start, end]`.
+        unsafe { self.end.offset_from(self.cursor) as usize }
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.cursor >= self.end
+    }
+
+    #[inline]
+    fn advance(&mut self, n: usize) {
+        debug_assert!(n <= self.remaining());
+        // SAFETY: callers only ever advance by an amount already checked
+        // against `remaining()`.
+        self.cursor = unsafe { self.cursor.add(n) };
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        if self.is_empty() {
+            None
+        } else {
+            // SAFETY: `cursor < end` was just checked.
+            Some(unsafe { *self.cursor })
+        }
+    }
+
+    #[inline]
+    #[allow(dead_code)]
+    fn peek_ahead(&self, offset: usize) -> Option<u8> {
+        if offset >= self.remaining() {
+            None
+        } else {
+            // SAFETY: `offset < remaining` was just checked.
+            Some(unsafe { *self.cursor.add(offset) })
+        }
+    }
+
+    /// Read a fixed-width chunk after a single length check, instead of
+    /// bounds-checking every individual byte in the chunk.
+    #[inline]
+    #[allow(dead_code)]
+    fn peek_n<const N: usize>(&self) -> Option<[u8; N]> {
+        if self.remaining() < N {
+            return None;
+        }
+        let mut out = [0u8; N];
+        // SAFETY: `remaining() >= N` was just checked, so reading N bytes
+        // from `cursor` stays within the original allocation.
+        unsafe {
+            std::ptr::copy_nonoverlapping(self.cursor, out.as_mut_ptr(), N);
+        }
+        Some(out)
+    }
+}
+
+const COMMENT_SENTINELS: [u8; 5] = [b'#', b'/', b'-', b'<', b';'];
+
+/// Count leading ASCII space/tab bytes, using AVX2 when the host supports
+/// it (x86_64) or NEON (aarch64, baseline on that target), and a scalar
+/// scan everywhere else.
+#[inline]
+fn count_leading_whitespace(bytes: &[u8]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just confirmed at runtime.
+            return unsafe { avx2::count_leading_whitespace(bytes) };
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: NEON is a baseline feature of aarch64.
+        return unsafe { neon::count_leading_whitespace(bytes) };
+    }
+    #[allow(unreachable_code)]
+    count_leading_whitespace_scalar(bytes)
+}
+
+fn count_leading_whitespace_scalar(bytes: &[u8]) -> usize {
+    let mut cursor = ByteCursor::new(bytes);
+    let mut n = 0;
+    while let Some(b) = cursor.peek() {
+        if b == b' ' || b == b'\t' {
+            cursor.advance(1);
+            n += 1;
+        } else {
+            break;
+        }
+    }
+    n
+}
+
+/// Find the offset of the first comment-sentinel byte in `bytes`, using
+/// AVX2/NEON when available and a scalar scan otherwise.
+#[inline]
+fn find_comment_sentinel(bytes: &[u8]) -> Option<usize> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just confirmed at runtime.
+            return unsafe { avx2::find_comment_sentinel(bytes) };
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        // SAFETY: NEON is a baseline feature of aarch64.
+        return unsafe { neon::find_comment_sentinel(bytes) };
+    }
+    #[allow(unreachable_code)]
+    find_comment_sentinel_scalar(bytes)
+}
+
+fn find_comment_sentinel_scalar(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|b| COMMENT_SENTINELS.contains(b))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use super::{count_leading_whitespace_scalar, find_comment_sentinel_scalar, COMMENT_SENTINELS};
+    use std::arch::x86_64::*;
+
+    /// Vectorized leading-whitespace trim: compares 32 bytes at a time
+    /// against `' '` and `'\t'`, and stops at the first chunk that isn't
+    /// entirely whitespace.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn count_leading_whitespace(bytes: &[u8]) -> usize {
+        let space = _mm256_set1_epi8(b' ' as i8);
+        let tab = _mm256_set1_epi8(b'\t' as i8);
+        let mut offset = 0usize;
+        while offset + 32 <= bytes.len() {
+            let chunk = _mm256_loadu_si256(bytes.as_ptr().add(offset) as *const __m256i);
+            let is_ws = _mm256_or_si256(
+                _mm256_cmpeq_epi8(chunk, space),
+                _mm256_cmpeq_epi8(chunk, tab),
+            );
+            let mask = _mm256_movemask_epi8(is_ws) as u32;
+            if mask != u32::MAX {
+                return offset + (!mask).trailing_zeros() as usize;
+            }
+            offset += 32;
+        }
+        offset + count_leading_whitespace_scalar(&bytes[offset..])
+    }
+
+    /// Scans 32 bytes at a time for any of the five comment-sentinel
+    /// bytes, producing a movemask of candidate offsets and returning the
+    /// first one set.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn find_comment_sentinel(bytes: &[u8]) -> Option<usize> {
+        let needles = COMMENT_SENTINELS.map(|s| _mm256_set1_epi8(s as i8));
+        let mut offset = 0usize;
+        while offset + 32 <= bytes.len() {
+            let chunk = _mm256_loadu_si256(bytes.as_ptr().add(offset) as *const __m256i);
+            let mut any = _mm256_setzero_si256();
+            for needle in needles {
+                any = _mm256_or_si256(any, _mm256_cmpeq_epi8(chunk, needle));
+            }
+            let mask = _mm256_movemask_epi8(any) as u32;
+            if mask != 0 {
+                return Some(offset + mask.trailing_zeros() as usize);
+            }
+            offset += 32;
+        }
+        find_comment_sentinel_scalar(&bytes[offset..]).map(|i| offset + i)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::{count_leading_whitespace_scalar, find_comment_sentinel_scalar, COMMENT_SENTINELS};
+    use std::arch::aarch64::*;
+
+    /// NEON has no cheap binary movemask, so instead of extracting one we
+    /// test a whole 16-byte chunk at once with `vminvq_u8` ("is every lane
+    /// nonzero") and only fall back to a byte-at-a-time scan for the one
+    /// chunk where the boundary actually lives.
+    pub unsafe fn count_leading_whitespace(bytes: &[u8]) -> usize {
+        let space = vdupq_n_u8(b' ');
+        let tab = vdupq_n_u8(b'\t');
+        let mut offset = 0usize;
+        while offset + 16 <= bytes.len() {
+            let chunk = vld1q_u8(bytes.as_ptr().add(offset));
+            let is_ws = vorrq_u8(vceqq_u8(chunk, space), vceqq_u8(chunk, tab));
+            if vminvq_u8(is_ws) != 0xFF {
+                break;
+            }
+            offset += 16;
+        }
+        offset + count_leading_whitespace_scalar(&bytes[offset..])
+    }
+
+    pub unsafe fn find_comment_sentinel(bytes: &[u8]) -> Option<usize> {
+        let mut offset = 0usize;
+        while offset + 16 <= bytes.len() {
+            let chunk = vld1q_u8(bytes.as_ptr().add(offset));
+            let mut any = vdupq_n_u8(0);
+            for s in COMMENT_SENTINELS {
+                any = vorrq_u8(any, vceqq_u8(chunk, vdupq_n_u8(s)));
+            }
+            if vmaxvq_u8(any) != 0 {
+                // A match is somewhere in this 16-byte chunk; pin down the
+                // exact offset with a short scalar scan.
+                if let Some(i) = find_comment_sentinel_scalar(&bytes[offset..offset + 16]) {
+                    return Some(offset + i);
+                }
+            }
+            offset += 16;
+        }
+        find_comment_sentinel_scalar(&bytes[offset..]).map(|i| offset + i)
+    }
+}
+
+// ── Data-driven keyword policy ──
+//
+// Separates *what* a keyword means (symbol, category, whether it needs a
+// trailing word boundary) from the trie that scans for it, so callers can
+// register keywords for languages this module doesn't know about.
+
+/// A single keyword rule: a byte string anchored at the start of a trimmed
+/// line, the `(PrefixSymbol, Category)` it maps to, and whether a word
+/// boundary must follow the match (false for literals that already embed
+/// their own boundary, like `"pub fn"`).
+type KeywordEntry = (&'static [u8], PrefixSymbol, Category, bool);
+
+/// Builder for a caller-extensible keyword policy. Register entries with
+/// [`KeywordTable::keyword`], or start from [`KeywordTable::builtin`] to get
+/// today's default Rust/Python/JS/C-family keyword set and extend it.
+#[derive(Default)]
+pub struct KeywordTable {
+    entries: Vec<KeywordEntry>,
+}
+
+impl KeywordTable {
+    /// Start an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a keyword: matched as a prefix of the trimmed line, mapped
+    /// to `symbol`/`category`. When `requires_boundary` is true, the byte
+    /// immediately following the match (if any) must satisfy [`is_boundary`]
+    /// for the match to count — this is what stops `"import"` from matching
+    /// inside `"important_value = 1"`.
+    pub fn keyword(
+        mut self,
+        bytes: &'static [u8],
+        symbol: PrefixSymbol,
+        category: Category,
+        requires_boundary: bool,
+    ) -> Self {
+        self.entries.push((bytes, symbol, category, requires_boundary));
+        self
+    }
+
+    /// The built-in keyword policy: the same Import/Declaration/Logic/
+    /// Modifier keyword set this module has always shipped with.
+    pub fn builtin() -> Self {
+        Self::new()
+            // Import
+            .keyword(b"import", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"from", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"use", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"require", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"#include", PrefixSymbol::N, Category::Import, false)
+            .keyword(b"using", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"extern", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"mod", PrefixSymbol::N, Category::Import, true)
+            .keyword(b"package", PrefixSymbol::N, Category::Import, true)
+            // Declaration
+            .keyword(b"fn", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"function", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"def", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"class", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"struct", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"enum", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"trait", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"const", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"let", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"var", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"type", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"static", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"impl", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"pub fn", PrefixSymbol::PlusOne, Category::Declaration, false)
+            .keyword(b"pub struct", PrefixSymbol::PlusOne, Category::Declaration, false)
+            .keyword(b"pub enum", PrefixSymbol::PlusOne, Category::Declaration, false)
+            .keyword(b"async fn", PrefixSymbol::PlusOne, Category::Declaration, false)
+            .keyword(b"export", PrefixSymbol::PlusOne, Category::Declaration, true)
+            // Logic
+            .keyword(b"if", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"else", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"elif", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"for", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"while", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"loop", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"match", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"switch", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"try", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"catch", PrefixSymbol::One, Category::Logic, true)
+            .keyword(b"} else", PrefixSymbol::One, Category::Logic, false)
+            // Modifier
+            .keyword(b"return", PrefixSymbol::PlusN, Category::Modifier, true)
+            .keyword(b"yield", PrefixSymbol::PlusN, Category::Modifier, true)
+            .keyword(b"break", PrefixSymbol::PlusN, Category::Modifier, true)
+            .keyword(b"continue", PrefixSymbol::PlusN, Category::Modifier, true)
+            .keyword(b"throw", PrefixSymbol::PlusN, Category::Modifier, true)
+            .keyword(b"raise", PrefixSymbol::PlusN, Category::Modifier, true)
+            .keyword(b"defer", PrefixSymbol::PlusN, Category::Modifier, true)
+    }
+
+    /// Compile into the dispatch structure the hot path scans: a goto trie
+    /// keyed by keyword bytes, with each terminal node remembering its
+    /// `(symbol, category, requires_boundary)`.
+    fn compile(self) -> CompiledKeywords {
+        CompiledKeywords::build(self.entries)
+    }
+}
+
+/// Compiled form of a [`KeywordTable`]: a goto trie over the registered
+/// keyword bytes, used for anchored (start-of-line) longest-match lookup.
+/// Unlike [`AhoCorasick`], this has no failure links — matches only ever
+/// start at position 0, so a plain trie walk suffices.
+struct CompiledKeywords {
+    goto_table: Vec<[Option<u32>; 256]>,
+    terminal: Vec<Option<KeywordEntry>>,
+}
+
+impl CompiledKeywords {
+    fn build(entries: Vec<KeywordEntry>) -> Self {
+        let mut goto_table = vec![[None; 256]];
+        let mut terminal = vec![None];
+
+        for entry in entries {
+            let mut state = AC_ROOT;
+            for &b in entry.0 {
+                state = match goto_table[state as usize][b as usize] {
+                    Some(next) => next,
+                    None => {
+                        goto_table.push([None; 256]);
+                        terminal.push(None);
+                        let next = (goto_table.len() - 1) as u32;
+                        goto_table[state as usize][b as usize] = Some(next);
+                        next
+                    }
+                };
+            }
+            terminal[state as usize] = Some(entry);
+        }
+
+        Self {
+            goto_table,
+            terminal,
+        }
+    }
+
+    /// Walk `bytes` from the start, returning the `(symbol, category)` of
+    /// the longest keyword whose boundary requirement (if any) is satisfied
+    /// by the byte immediately following the match.
+    #[inline]
+    fn match_at_start(&self, bytes: &[u8]) -> Option<(PrefixSymbol, Category)> {
+        let mut state = AC_ROOT;
+        let mut best = None;
+        for (i, &b) in bytes.iter().enumerate() {
+            state = match self.goto_table[state as usize][b as usize] {
+                Some(next) => next,
+                None => break,
+            };
+            if let Some((_, symbol, category, requires_boundary)) = self.terminal[state as usize] {
+                let next_pos = i + 1;
+                let boundary_ok = !requires_boundary
+                    || next_pos >= bytes.len()
+                    || is_boundary(bytes[next_pos]);
+                if boundary_ok {
+                    best = Some((symbol, category));
+                }
+            }
+        }
+        best
+    }
 }
 
 // ── I/O patterns for SIMD-friendly substring matching ──
@@ -261,24 +807,102 @@ const IO_PATTERNS: [&[u8]; 12] = [
     b"stderr",
 ];
 
-/// SIMD-friendly multi-pattern substring search.
-/// Checks if any pattern exists in the haystack.
-#[inline]
-fn contains_any_simd_friendly(haystack: &[u8], patterns: &[&[u8]]) -> bool {
-    // Short-circuit: if haystack is very short, direct scan
-    for pat in patterns {
-        if pat.len() > haystack.len() {
-            continue;
-        }
-        // Window scan — compiler auto-vectorizes this for SIMD
-        let limit = haystack.len() - pat.len() + 1;
-        for i in 0..limit {
-            if &haystack[i..i + pat.len()] == *pat {
+/// Root state of every Aho-Corasick automaton.
+const AC_ROOT: u32 = 0;
+
+/// Compiled multi-pattern automaton (goto trie + failure links + output sets)
+/// for single-pass substring matching against a fixed pattern set.
+///
+/// Built once (see [`AhoCorasick::build`]) and reused across every
+/// classification call, so the O(patterns * total haystack length) cost of a
+/// naive window scan collapses to a single left-to-right pass over the line.
+struct AhoCorasick {
+    /// `goto[state][byte]` is the next state, or `None` if there is no edge.
+    goto_table: Vec<[Option<u32>; 256]>,
+    /// Failure link for each state, used when `goto` has no edge for a byte.
+    fail: Vec<u32>,
+    /// Whether reaching this state means some pattern has matched, which is
+    /// true either because the state itself terminates a pattern, or because
+    /// a state reachable via its failure chain does.
+    output: Vec<bool>,
+}
+
+impl AhoCorasick {
+    /// Build the automaton from a fixed pattern set: insert each pattern into
+    /// a goto trie, compute failure links via a BFS from the root, and union
+    /// output sets down the failure chain.
+    fn build(patterns: &[&[u8]]) -> Self {
+        let mut goto_table = vec![[None; 256]];
+        let mut output = vec![false];
+
+        for pat in patterns {
+            let mut state = AC_ROOT;
+            for &b in *pat {
+                state = match goto_table[state as usize][b as usize] {
+                    Some(next) => next,
+                    None => {
+                        goto_table.push([None; 256]);
+                        output.push(false);
+                        let next = (goto_table.len() - 1) as u32;
+                        goto_table[state as usize][b as usize] = Some(next);
+                        next
+                    }
+                };
+            }
+            output[state as usize] = true;
+        }
+
+        let mut fail = vec![AC_ROOT; goto_table.len()];
+        let mut queue = VecDeque::new();
+        for s in goto_table[AC_ROOT as usize].into_iter().flatten() {
+            fail[s as usize] = AC_ROOT;
+            queue.push_back(s);
+        }
+        while let Some(u) = queue.pop_front() {
+            for (b, slot) in goto_table[u as usize].into_iter().enumerate() {
+                let Some(v) = slot else {
+                    continue;
+                };
+                // Follow u's failure chain until we find a node with a goto
+                // edge on `b` (root always qualifies, since its "missing"
+                // edges are treated as self-loops below).
+                let mut f = fail[u as usize];
+                while f != AC_ROOT && goto_table[f as usize][b].is_none() {
+                    f = fail[f as usize];
+                }
+                fail[v as usize] = match goto_table[f as usize][b] {
+                    Some(target) if target != v => target,
+                    _ => AC_ROOT,
+                };
+                output[v as usize] = output[v as usize] || output[fail[v as usize] as usize];
+                queue.push_back(v);
+            }
+        }
+
+        Self {
+            goto_table,
+            fail,
+            output,
+        }
+    }
+
+    /// Walk the haystack once, following goto edges and falling back through
+    /// failure links, reporting a hit as soon as any state's output set is
+    /// non-empty.
+    #[inline]
+    fn is_match(&self, haystack: &[u8]) -> bool {
+        let mut state = AC_ROOT;
+        for &b in haystack {
+            while state != AC_ROOT && self.goto_table[state as usize][b as usize].is_none() {
+                state = self.fail[state as usize];
+            }
+            state = self.goto_table[state as usize][b as usize].unwrap_or(AC_ROOT);
+            if self.output[state as usize] {
                 return true;
             }
         }
+        false
     }
-    false
 }
 
 /// Fast assignment detection — scans for '=' not part of '==' or '==='
@@ -412,4 +1036,166 @@ mod tests {
         assert!(is_boundary(b'('));
         assert!(!is_boundary(b'a'));
     }
+
+    #[test]
+    fn test_aho_corasick_matches_any_pattern() {
+        let ac = AhoCorasick::build(&IO_PATTERNS);
+        assert!(ac.is_match(b"console.log(x)"));
+        assert!(ac.is_match(b"print('hi')"));
+        assert!(ac.is_match(b"await fetch('/api')"));
+        assert!(!ac.is_match(b"let x = 42;"));
+    }
+
+    #[test]
+    fn test_keyword_table_custom_language() {
+        // A caller registering Ruby's `def`/`end` and `require` without
+        // touching this module at all.
+        let table = KeywordTable::new()
+            .keyword(b"def", PrefixSymbol::PlusOne, Category::Declaration, true)
+            .keyword(b"require", PrefixSymbol::N, Category::Import, true);
+        let c = SimdClassifier::with_table(table);
+
+        assert_eq!(
+            c.classify_line_fast("def greet(name)").0,
+            PrefixSymbol::PlusOne
+        );
+        assert_eq!(
+            c.classify_line_fast("require 'json'").0,
+            PrefixSymbol::N
+        );
+        // Keywords outside the registered set fall through to Unknown.
+        assert_eq!(c.classify_line_fast("puts 'hi'").0, PrefixSymbol::MinusN);
+    }
+
+    #[test]
+    fn test_keyword_table_respects_boundary() {
+        let table = KeywordTable::new().keyword(
+            b"import",
+            PrefixSymbol::N,
+            Category::Import,
+            true,
+        );
+        let c = SimdClassifier::with_table(table);
+        assert_eq!(c.classify_line_fast("import os").0, PrefixSymbol::N);
+        // "important" has "import" as a prefix but no boundary after it.
+        assert_eq!(
+            c.classify_line_fast("important = true").0,
+            PrefixSymbol::PlusZero
+        );
+    }
+
+    #[test]
+    fn test_keyword_table_builtin_matches_default_constructor() {
+        let built = SimdClassifier::with_table(KeywordTable::builtin());
+        let default_ctor = SimdClassifier::new();
+        for line in ["fn main() {", "import os", "if x {", "return 1"] {
+            assert_eq!(
+                built.classify_line_fast(line).0,
+                default_ctor.classify_line_fast(line).0
+            );
+        }
+    }
+
+    #[test]
+    fn test_aho_corasick_overlapping_patterns() {
+        // "consol.log(1)" follows the "console." trie path for six bytes and
+        // fails on '.'; the automaton must fall back through its failure
+        // links and still find ".log(" starting at that same byte.
+        let ac = AhoCorasick::build(&IO_PATTERNS);
+        assert!(ac.is_match(b"consol.log(1)"));
+        assert!(ac.is_match(b"x.log(1)"));
+        assert!(!ac.is_match(b"x.info(1)"));
+    }
+
+    #[test]
+    fn test_byte_cursor_peek_and_advance() {
+        let mut cursor = ByteCursor::new(b"abc");
+        assert_eq!(cursor.peek(), Some(b'a'));
+        assert_eq!(cursor.peek_ahead(2), Some(b'c'));
+        assert_eq!(cursor.peek_ahead(3), None);
+        assert_eq!(cursor.peek_n::<2>(), Some([b'a', b'b']));
+        cursor.advance(3);
+        assert!(cursor.is_empty());
+        assert_eq!(cursor.peek(), None);
+    }
+
+    #[test]
+    fn test_count_leading_whitespace_matches_scalar() {
+        for input in ["", "   x", "\t\tx", "no_leading_ws", &" ".repeat(40), "    "] {
+            assert_eq!(
+                count_leading_whitespace(input.as_bytes()),
+                count_leading_whitespace_scalar(input.as_bytes()),
+                "input: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_comment_sentinel_matches_scalar() {
+        let long_prefix = "x".repeat(40);
+        for input in [
+            "# comment",
+            "no sentinel here",
+            &(long_prefix.clone() + "# trailing"),
+            &long_prefix,
+        ] {
+            assert_eq!(
+                find_comment_sentinel(input.as_bytes()),
+                find_comment_sentinel_scalar(input.as_bytes()),
+                "input: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_scan_comment_candidates_only_matches_line_starts() {
+        let c = SimdClassifier::new();
+        let source = "let x = 1; # not a line start\n# real comment\ny = 2";
+        let candidates = c.scan_comment_candidates(source);
+        let expected = source.find("# real comment").unwrap();
+        assert_eq!(candidates, vec![expected]);
+    }
+
+    #[test]
+    fn test_classify_stream_matches_classify_batch() {
+        let c = SimdClassifier::new();
+        let source = "import os\n\ndef main():\n    print('hi')\n    x = 1\n    return x";
+
+        let batch = c.classify_batch(&source.lines().collect::<Vec<_>>());
+        let streamed: Vec<ClassifyResult> = c
+            .classify_stream(source.as_bytes())
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(streamed.len(), batch.len());
+        for (s, b) in streamed.iter().zip(batch.iter()) {
+            assert_eq!(s.symbol, b.symbol);
+            assert_eq!(s.category, b.category);
+        }
+    }
+
+    #[test]
+    fn test_classify_stream_without_trailing_newline() {
+        let c = SimdClassifier::new();
+        let results: Vec<ClassifyResult> = c
+            .classify_stream("count += 1".as_bytes())
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol, "+0");
+    }
+
+    #[test]
+    fn test_classify_stream_handles_line_wider_than_block() {
+        let c = SimdClassifier::new();
+        let long_line = format!("x = \"{}\"", "a".repeat(STREAM_BLOCK_SIZE * 2));
+        let source = format!("{long_line}\nreturn x");
+        let results: Vec<ClassifyResult> = c
+            .classify_stream(source.as_bytes())
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].symbol, "+0");
+        assert_eq!(results[1].symbol, "+n");
+    }
 }
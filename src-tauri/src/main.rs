@@ -6,6 +6,7 @@
 
 use tauri::Manager;
 use tauri::menu::{MenuBuilder, SubmenuBuilder, MenuItemBuilder};
+use base64::Engine;
 
 // ──────────────────────────────────────────────────────────
 // Device frame presets (width, height)
@@ -79,6 +80,88 @@ fn resolve_device(device: &str) -> Option<(f64, f64)> {
     }
 }
 
+/// Canonical (device name, size) pairs used to reverse-match a frame's
+/// current logical size back to a named preset from `resolve_device`.
+const DEVICE_PRESETS: &[(&str, (f64, f64))] = &[
+    ("iphone", DEVICE_IPHONE),
+    ("iphone-se", DEVICE_IPHONE_SE),
+    ("iphone-pm", DEVICE_IPHONE_PM),
+    ("galaxy", DEVICE_GALAXY_S24),
+    ("pixel", DEVICE_PIXEL_8),
+    ("ipad", DEVICE_IPAD),
+    ("ipad-mini", DEVICE_IPAD_MINI),
+    ("ipad-pro", DEVICE_IPAD_PRO),
+    ("macbook", DEVICE_MACBOOK),
+    ("desktop", DEVICE_DESKTOP),
+    ("1080p", DEVICE_1080P),
+    ("1440p", DEVICE_1440P),
+    ("quest3", DEVICE_QUEST3),
+    ("quest-pro", DEVICE_QUEST_PRO),
+    ("raybans", DEVICE_RAYBANS),
+    ("vision", DEVICE_VISION_PRO),
+    ("feed", DEVICE_FEED),
+    ("feed-wide", DEVICE_FEED_WIDE),
+];
+
+/// Reverse-match a logical (width, height) against the preset table,
+/// returning the first device name whose size matches within half a pixel.
+fn reverse_resolve_device(width: f64, height: f64) -> Option<&'static str> {
+    DEVICE_PRESETS
+        .iter()
+        .find(|(_, (w, h))| (w - width).abs() < 0.5 && (h - height).abs() < 0.5)
+        .map(|(name, _)| *name)
+}
+
+
+// ──────────────────────────────────────────────────────────
+// Window lifecycle events
+// ──────────────────────────────────────────────────────────
+
+/// Emit `window-state-changed` with the window's current size, focus state,
+/// and (if it matches a known preset) the device name it currently represents.
+fn emit_window_state(app: &tauri::AppHandle, window: &tauri::WebviewWindow) {
+    let label = window.label().to_string();
+    let size = window.inner_size().unwrap_or_default();
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let logical = size.to_logical::<f64>(scale);
+    let focused = window.is_focused().unwrap_or(false);
+    let device = reverse_resolve_device(logical.width, logical.height);
+
+    let _ = app.emit("window-state-changed", serde_json::json!({
+        "label": label,
+        "width": logical.width,
+        "height": logical.height,
+        "focused": focused,
+        "device": device,
+    }));
+}
+
+/// Subscribe a newly-created webview window to resize/focus/move/close
+/// events so the launcher can maintain a live grid without polling
+/// `list_windows`. Also keeps the tray's "Windows" submenu in sync: this
+/// window just appeared, so the tray is refreshed immediately, and again
+/// whenever it closes.
+fn register_window_lifecycle_events(app: &tauri::AppHandle, window: &tauri::WebviewWindow) {
+    let app_handle = app.clone();
+    let win = window.clone();
+    window.on_window_event(move |event| {
+        match event {
+            tauri::WindowEvent::Resized(_)
+            | tauri::WindowEvent::Focused(_)
+            | tauri::WindowEvent::Moved(_) => {
+                emit_window_state(&app_handle, &win);
+            }
+            tauri::WindowEvent::CloseRequested { .. } => {
+                emit_window_state(&app_handle, &win);
+            }
+            tauri::WindowEvent::Destroyed => {
+                refresh_tray_menu(&app_handle);
+            }
+            _ => {}
+        }
+    });
+    refresh_tray_menu(app);
+}
 
 // ──────────────────────────────────────────────────────────
 // Tauri commands
@@ -195,12 +278,15 @@ fn open_window(
     .center()
     .build()
     {
-        Ok(_) => Ok(serde_json::json!({
-            "label": final_label,
-            "device": device,
-            "width": width,
-            "height": height,
-        })),
+        Ok(window) => {
+            register_window_lifecycle_events(&app, &window);
+            Ok(serde_json::json!({
+                "label": final_label,
+                "device": device,
+                "width": width,
+                "height": height,
+            }))
+        }
         Err(e) => Err(format!("Failed to create window: {}", e)),
     }
 }
@@ -209,6 +295,7 @@ fn open_window(
 #[tauri::command]
 fn set_device_frame(
     app: tauri::AppHandle,
+    chrome_state: tauri::State<FrameChromeState>,
     label: String,
     device: String,
 ) -> Result<serde_json::Value, String> {
@@ -221,6 +308,13 @@ fn set_device_frame(
         window.set_title(&format!("hexterm — {} ({}x{})", device, width as u32, height as u32))
             .map_err(|e| format!("{}", e))?;
         window.center().map_err(|e| format!("{}", e))?;
+
+        // Re-apply whatever chrome style the user previously chose for this
+        // label, so switching presets doesn't reset a borderless mockup.
+        if let Some(style) = chrome_state.0.lock().unwrap().get(&label).cloned() {
+            let _ = apply_frame_chrome(&window, &style);
+        }
+
         Ok(serde_json::json!({
             "label": label,
             "device": device,
@@ -232,6 +326,56 @@ fn set_device_frame(
     }
 }
 
+/// Per-label title-bar chrome style, persisted so re-applying a device
+/// preset via `set_device_frame` keeps the chrome mode the user selected.
+#[derive(Default)]
+struct FrameChromeState(std::sync::Mutex<std::collections::HashMap<String, String>>);
+
+/// Apply a title-bar chrome style (`visible`, `transparent`, `overlay`) to a
+/// window. `overlay` is only meaningful on macOS; other platforms fall back
+/// to showing/hiding the native decorations.
+#[cfg(target_os = "macos")]
+fn apply_frame_chrome(window: &tauri::WebviewWindow, style: &str) -> Result<(), String> {
+    let title_bar_style = match style {
+        "transparent" => tauri::TitleBarStyle::Transparent,
+        "overlay" => tauri::TitleBarStyle::Overlay,
+        _ => tauri::TitleBarStyle::Visible,
+    };
+    window
+        .set_title_bar_style(title_bar_style)
+        .map_err(|e| format!("{}", e))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn apply_frame_chrome(window: &tauri::WebviewWindow, style: &str) -> Result<(), String> {
+    window
+        .set_decorations(style == "visible")
+        .map_err(|e| format!("{}", e))
+}
+
+/// Switch a window's title-bar style at runtime on top of `set_device_frame`,
+/// so a device preset can be shown borderless for a mockup or restored for
+/// debugging.
+#[tauri::command]
+fn set_frame_chrome(
+    app: tauri::AppHandle,
+    chrome_state: tauri::State<FrameChromeState>,
+    label: String,
+    style: String,
+) -> Result<serde_json::Value, String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window '{}' not found", label))?;
+
+    apply_frame_chrome(&window, &style)?;
+    chrome_state.0.lock().unwrap().insert(label.clone(), style.clone());
+
+    Ok(serde_json::json!({
+        "label": label,
+        "style": style,
+    }))
+}
+
 /// List all open windows
 #[tauri::command]
 fn list_windows(app: tauri::AppHandle) -> Vec<serde_json::Value> {
@@ -248,6 +392,208 @@ fn list_windows(app: tauri::AppHandle) -> Vec<serde_json::Value> {
         .collect()
 }
 
+/// Capture a single window's rendered contents as an encoded image, using
+/// the window's outer bounds to locate it on screen.
+fn capture_window_image(window: &tauri::WebviewWindow, format: &str) -> Result<(String, u32, u32), String> {
+    let scale = window.scale_factor().map_err(|e| format!("{}", e))?;
+    let outer_pos = window.outer_position().map_err(|e| format!("{}", e))?;
+    let outer_size = window.outer_size().map_err(|e| format!("{}", e))?;
+
+    // Allow a couple of pixels of slack: the float `scale` multiply-and-round
+    // here and xcap's own enumeration can each introduce a 1px rounding
+    // discrepancy against Tauri's `outer_position()`, and strict equality
+    // turned that into a spurious "could not locate" error on otherwise
+    // valid windows.
+    const POSITION_TOLERANCE_PX: i32 = 2;
+    let xcap_window = xcap::Window::all()
+        .map_err(|e| format!("Failed to enumerate windows: {}", e))?
+        .into_iter()
+        .find(|w| {
+            let wx = (w.x() as f64 * scale).round() as i32;
+            let wy = (w.y() as f64 * scale).round() as i32;
+            (wx - outer_pos.x).abs() <= POSITION_TOLERANCE_PX
+                && (wy - outer_pos.y).abs() <= POSITION_TOLERANCE_PX
+        })
+        .ok_or_else(|| format!("Could not locate '{}' on screen", window.label()))?;
+
+    let image = xcap_window
+        .capture_image()
+        .map_err(|e| format!("Capture failed: {}", e))?;
+
+    let mut bytes: Vec<u8> = Vec::new();
+    let encoded_format = match format {
+        "jpeg" | "jpg" => image::ImageFormat::Jpeg,
+        _ => image::ImageFormat::Png,
+    };
+    image::DynamicImage::ImageRgba8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), encoded_format)
+        .map_err(|e| format!("Encode failed: {}", e))?;
+
+    let mime = match encoded_format {
+        image::ImageFormat::Jpeg => "image/jpeg",
+        _ => "image/png",
+    };
+    let data_url = format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    );
+
+    Ok((data_url, outer_size.width, outer_size.height))
+}
+
+/// Capture what a single emulated frame currently renders, encoded as a
+/// base64 data URL (PNG or JPEG per `format`).
+#[tauri::command]
+fn capture_frame(app: tauri::AppHandle, label: String, format: String) -> Result<serde_json::Value, String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window '{}' not found", label))?;
+    let (data_url, width, height) = capture_window_image(&window, &format)?;
+
+    Ok(serde_json::json!({
+        "label": label,
+        "format": format,
+        "dataUrl": data_url,
+        "width": width,
+        "height": height,
+    }))
+}
+
+/// Capture every open device frame in one pass, tagged with its label and
+/// reverse-resolved device name — a one-click device contact sheet.
+#[tauri::command]
+fn capture_all_frames(app: tauri::AppHandle) -> Vec<serde_json::Value> {
+    app.webview_windows()
+        .into_iter()
+        .filter_map(|(label, window)| {
+            let (data_url, width, height) = capture_window_image(&window, "png").ok()?;
+            let scale = window.scale_factor().unwrap_or(1.0);
+            let logical = window.inner_size().unwrap_or_default().to_logical::<f64>(scale);
+            let device = reverse_resolve_device(logical.width, logical.height);
+            Some(serde_json::json!({
+                "label": label,
+                "device": device,
+                "dataUrl": data_url,
+                "width": width,
+                "height": height,
+            }))
+        })
+        .collect()
+}
+
+/// Report the global cursor position in a frame's device-logical coordinate
+/// space, so the JS layer can overlay a simulated touch indicator normalized
+/// to whatever preset the frame is currently sized to.
+#[tauri::command]
+fn cursor_position(app: tauri::AppHandle, label: String) -> Result<serde_json::Value, String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window '{}' not found", label))?;
+
+    let scale = window.scale_factor().map_err(|e| format!("{}", e))?;
+    let inner_pos = window.inner_position().map_err(|e| format!("{}", e))?;
+    let inner_size = window.inner_size().map_err(|e| format!("{}", e))?;
+
+    let device_state = device_query::DeviceState::new();
+    let (cursor_x, cursor_y) = device_query::DeviceQuery::get_mouse(&device_state).coords;
+
+    // Physical cursor position relative to the window's content origin (not
+    // `outer_position()`, which includes the title bar/border and would
+    // offset every reading by the chrome size on decorated frames),
+    // converted down to the frame's logical coordinate space.
+    let rel_x = (cursor_x - inner_pos.x) as f64 / scale;
+    let rel_y = (cursor_y - inner_pos.y) as f64 / scale;
+    let logical_size = inner_size.to_logical::<f64>(scale);
+
+    let inside = rel_x >= 0.0 && rel_y >= 0.0 && rel_x <= logical_size.width && rel_y <= logical_size.height;
+
+    Ok(serde_json::json!({
+        "x": rel_x,
+        "y": rel_y,
+        "inside": inside,
+    }))
+}
+
+/// Move an existing webview into a different container window, so a
+/// detached device frame can be re-docked into a grid layout (or vice versa).
+#[tauri::command]
+fn reparent_window(
+    app: tauri::AppHandle,
+    label: String,
+    new_parent_label: String,
+) -> Result<serde_json::Value, String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window '{}' not found", label))?;
+    let new_parent = app
+        .get_webview_window(&new_parent_label)
+        .ok_or_else(|| format!("Window '{}' not found", new_parent_label))?;
+
+    window
+        .reparent(&new_parent)
+        .map_err(|e| format!("Failed to reparent '{}': {}", label, e))?;
+
+    let size = window.inner_size().map_err(|e| format!("{}", e))?;
+    Ok(serde_json::json!({
+        "label": label,
+        "newParent": new_parent_label,
+        "width": size.width,
+        "height": size.height,
+    }))
+}
+
+/// Detach a webview from its current parent into a fresh top-level window,
+/// preserving its current size.
+#[tauri::command]
+fn detach_window(app: tauri::AppHandle, label: String) -> Result<serde_json::Value, String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window '{}' not found", label))?;
+    let size = window.inner_size().map_err(|e| format!("{}", e))?;
+
+    let detached_label = format!(
+        "{}-detached-{}",
+        label,
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+            % 10000
+    );
+
+    let detached = tauri::WebviewWindowBuilder::new(
+        &app,
+        &detached_label,
+        tauri::WebviewUrl::App("blank.html".into()),
+    )
+    .title(&label)
+    .inner_size(size.width as f64, size.height as f64)
+    .resizable(true)
+    .center()
+    .build()
+    .map_err(|e| format!("Failed to create detached window: {}", e))?;
+
+    // `register_window_lifecycle_events` is what keeps the tray's `Windows`
+    // submenu (built by `build_tray_windows_submenu`) from drifting — without
+    // it, closing this new container later wouldn't trigger a
+    // `refresh_tray_menu` and it would linger in the tray under a stale
+    // label even though `list_windows` (which queries live) had already
+    // stopped reporting it.
+    register_window_lifecycle_events(&app, &detached);
+
+    window
+        .reparent(&detached)
+        .map_err(|e| format!("Failed to detach '{}': {}", label, e))?;
+
+    Ok(serde_json::json!({
+        "label": label,
+        "newParent": detached_label,
+        "width": size.width,
+        "height": size.height,
+    }))
+}
+
 /// Open a lightweight feed window (video/audio/transcript, no terminal)
 #[tauri::command]
 fn open_feed(
@@ -283,12 +629,15 @@ fn open_feed(
     .center()
     .build()
     {
-        Ok(_) => Ok(serde_json::json!({
-            "feedId": final_label,
-            "source": source,
-            "width": width,
-            "height": height,
-        })),
+        Ok(window) => {
+            register_window_lifecycle_events(&app, &window);
+            Ok(serde_json::json!({
+                "feedId": final_label,
+                "source": source,
+                "width": width,
+                "height": height,
+            }))
+        }
         Err(e) => Err(format!("Failed to create feed window: {}", e)),
     }
 }
@@ -377,6 +726,9 @@ fn build_menu(app: &tauri::App) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn
         .item(&MenuItemBuilder::with_id("zoom-out", "Zoom Out")
             .accelerator("CmdOrCtrl+-").build(app)?)
         .separator()
+        .item(&MenuItemBuilder::with_id("toggle-frame-chrome", "Toggle Frame Chrome")
+            .accelerator("CmdOrCtrl+Shift+B").build(app)?)
+        .separator()
         .item(&device_menu)
         .build()?;
 
@@ -420,6 +772,195 @@ fn build_menu(app: &tauri::App) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn
 }
 
 
+// ──────────────────────────────────────────────────────────
+// System tray
+// ──────────────────────────────────────────────────────────
+
+/// Build the tray menu: a quick-switch mirror of the `Device` submenu plus
+/// a live "Windows" submenu listing every currently open frame. Generic
+/// over `Manager` so it can run both at startup (`&tauri::App`) and later,
+/// from [`refresh_tray_menu`], off a plain `&tauri::AppHandle`.
+fn build_tray_menu<M: tauri::Manager<tauri::Wry>>(app: &M) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn std::error::Error>> {
+    let device_menu = SubmenuBuilder::new(app, "Device")
+        // Phones
+        .item(&MenuItemBuilder::with_id("tray-dev-iphone", "iPhone 15 Pro (393x852)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-iphone-se", "iPhone SE (375x667)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-iphone-pm", "iPhone 15 Pro Max (430x932)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-galaxy", "Galaxy S24 (360x780)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-pixel", "Pixel 8 (412x915)").build(app)?)
+        .separator()
+        // Tablets
+        .item(&MenuItemBuilder::with_id("tray-dev-ipad", "iPad Air (820x1180)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-ipad-pro", "iPad Pro 12.9 (1024x1366)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-ipad-mini", "iPad mini (744x1133)").build(app)?)
+        .separator()
+        // Desktop
+        .item(&MenuItemBuilder::with_id("tray-dev-macbook", "MacBook Air (1440x900)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-desktop", "Desktop (1400x900)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-1080p", "Full HD (1920x1080)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-1440p", "QHD (2560x1440)").build(app)?)
+        .separator()
+        // XR
+        .item(&MenuItemBuilder::with_id("tray-dev-quest3", "Meta Quest 3 (2064x2208)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-quest-pro", "Meta Quest Pro (1800x1920)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-raybans", "Meta Ray-Ban (1280x960)").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-dev-vision", "Apple Vision Pro (3660x3200)").build(app)?)
+        .separator()
+        // Feed
+        .item(&MenuItemBuilder::with_id("tray-dev-feed", "Feed (480x640)").build(app)?)
+        .build()?;
+
+    let windows_menu = build_tray_windows_submenu(app)?;
+
+    let menu = MenuBuilder::new(app)
+        .item(&device_menu)
+        .item(&windows_menu)
+        .separator()
+        .item(&MenuItemBuilder::with_id("tray-new-window", "New Terminal").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-new-grid", "New Grid View").build(app)?)
+        .item(&MenuItemBuilder::with_id("tray-show-launcher", "Show Launcher").build(app)?)
+        .separator()
+        .item(&MenuItemBuilder::with_id("tray-quit", "Quit").build(app)?)
+        .build()?;
+
+    Ok(menu)
+}
+
+/// Build a submenu listing every currently open window, keyed by label, so
+/// clicking an entry focuses it. Rebuilt by [`build_tray_menu`] — at
+/// startup, and again via [`refresh_tray_menu`] whenever a window registered
+/// through `register_window_lifecycle_events` opens or closes, and whenever
+/// the tray icon is clicked.
+fn build_tray_windows_submenu<M: tauri::Manager<tauri::Wry>>(app: &M) -> Result<tauri::menu::Submenu<tauri::Wry>, Box<dyn std::error::Error>> {
+    let mut builder = SubmenuBuilder::new(app, "Windows");
+    let windows = app.webview_windows();
+    if windows.is_empty() {
+        builder = builder.item(&MenuItemBuilder::with_id("tray-no-windows", "(no windows open)")
+            .enabled(false)
+            .build(app)?);
+    } else {
+        for label in windows.keys() {
+            builder = builder.item(&MenuItemBuilder::with_id(format!("tray-focus-{}", label), label.as_str()).build(app)?);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Build and attach the system tray icon, mirroring the `Device` submenu so
+/// presets remain reachable while the app is minimized or backgrounded.
+fn build_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app)?;
+
+    tauri::tray::TrayIconBuilder::with_id("main-tray")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .tooltip("uvspeed")
+        .on_menu_event(handle_tray_event)
+        .on_tray_icon_event(|tray, event| {
+            // Rebuild the "Windows" submenu right before the native menu pops
+            // up, so a window opened/closed through some path other than
+            // `register_window_lifecycle_events` is still reflected.
+            if let tauri::tray::TrayIconEvent::Click { .. } = event {
+                refresh_tray_menu(tray.app_handle());
+            }
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+/// Rebuild the tray menu from the current window list and re-set it on the
+/// `"main-tray"` icon. [`build_tray_windows_submenu`]'s "(no windows open)" /
+/// per-window entries are only ever correct at the instant they're built —
+/// this is what keeps them correct after windows open or close instead of
+/// only at startup.
+fn refresh_tray_menu(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return;
+    };
+    if let Ok(menu) = build_tray_menu(app) {
+        let _ = tray.set_menu(Some(menu));
+    }
+}
+
+fn handle_tray_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
+    let id = event.id().0.as_str();
+
+    if let Some(label) = id.strip_prefix("tray-focus-") {
+        if let Some(w) = app.get_webview_window(label) {
+            let _ = w.set_focus();
+        }
+        return;
+    }
+
+    if let Some(device_name) = id.strip_prefix("tray-dev-") {
+        resize_focused_to(app, device_name);
+        return;
+    }
+
+    match id {
+        "tray-new-window" => {
+            let label = format!("win-{}", std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default().as_millis() % 100000);
+            if let Ok(window) = tauri::WebviewWindowBuilder::new(
+                app,
+                &label,
+                tauri::WebviewUrl::App("terminal.html".into()),
+            )
+            .title("uvspeed — terminal")
+            .inner_size(820.0, 1080.0)
+            .min_inner_size(280.0, 400.0)
+            .resizable(true)
+            .center()
+            .build()
+            {
+                register_window_lifecycle_events(app, &window);
+            }
+        }
+        "tray-new-grid" => {
+            let label = format!("grid-{}", std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default().as_millis() % 100000);
+            if let Ok(window) = tauri::WebviewWindowBuilder::new(
+                app,
+                &label,
+                tauri::WebviewUrl::App("grid.html".into()),
+            )
+            .title("uvspeed — grid")
+            .inner_size(1400.0, 900.0)
+            .min_inner_size(400.0, 300.0)
+            .resizable(true)
+            .center()
+            .build()
+            {
+                register_window_lifecycle_events(app, &window);
+            }
+        }
+        "tray-show-launcher" => {
+            if let Some(w) = app.get_webview_window("launcher") {
+                let _ = w.set_focus();
+            } else if let Ok(window) = tauri::WebviewWindowBuilder::new(
+                app,
+                "launcher",
+                tauri::WebviewUrl::App("launcher.html".into()),
+            )
+            .title("hexterm")
+            .inner_size(580.0, 560.0)
+            .min_inner_size(400.0, 420.0)
+            .resizable(true)
+            .center()
+            .build()
+            {
+                register_window_lifecycle_events(app, &window);
+            }
+        }
+        "tray-quit" => app.exit(0),
+        _ => {}
+    }
+}
+
+
 // ──────────────────────────────────────────────────────────
 // Menu event handler
 // ──────────────────────────────────────────────────────────
@@ -439,6 +980,19 @@ fn focused_window(app: &tauri::AppHandle) -> Option<tauri::WebviewWindow> {
         .or_else(|| windows.into_values().next())
 }
 
+/// Resize the focused window to a named device preset (shared by the menu
+/// bar and the tray's quick-switch menu).
+fn resize_focused_to(app: &tauri::AppHandle, device_name: &str) {
+    if let Some((w, h)) = resolve_device(device_name) {
+        if let Some(win) = focused_window(app) {
+            let size = tauri::LogicalSize::new(w, h);
+            let _ = win.set_size(tauri::Size::Logical(size));
+            let _ = win.set_title(&format!("hexterm — {} ({}x{})", device_name, w as u32, h as u32));
+            let _ = win.center();
+        }
+    }
+}
+
 fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
     let id = event.id().0.as_str();
 
@@ -449,17 +1003,7 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
         }
     };
 
-    // Device resize helper
-    let resize_to = |device_name: &str| {
-        if let Some((w, h)) = resolve_device(device_name) {
-            if let Some(win) = focused_window(app) {
-                let size = tauri::LogicalSize::new(w, h);
-                let _ = win.set_size(tauri::Size::Logical(size));
-                let _ = win.set_title(&format!("hexterm — {} ({}x{})", device_name, w as u32, h as u32));
-                let _ = win.center();
-            }
-        }
-    };
+    let resize_to = |device_name: &str| resize_focused_to(app, device_name);
 
     match id {
         // ── File ──
@@ -467,7 +1011,7 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
             let label = format!("win-{}", std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default().as_millis() % 100000);
-            let _ = tauri::WebviewWindowBuilder::new(
+            if let Ok(window) = tauri::WebviewWindowBuilder::new(
                 app,
                 &label,
                 tauri::WebviewUrl::App("terminal.html".into()),
@@ -477,13 +1021,16 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
             .min_inner_size(280.0, 400.0)
             .resizable(true)
             .center()
-            .build();
+            .build()
+            {
+                register_window_lifecycle_events(app, &window);
+            }
         }
         "new-grid" => {
             let label = format!("grid-{}", std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default().as_millis() % 100000);
-            let _ = tauri::WebviewWindowBuilder::new(
+            if let Ok(window) = tauri::WebviewWindowBuilder::new(
                 app,
                 &label,
                 tauri::WebviewUrl::App("grid.html".into()),
@@ -493,7 +1040,10 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
             .min_inner_size(400.0, 300.0)
             .resizable(true)
             .center()
-            .build();
+            .build()
+            {
+                register_window_lifecycle_events(app, &window);
+            }
         }
         "show-launcher" => {
             // Focus or re-open the launcher
@@ -541,6 +1091,24 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
         "zoom-reset" => eval_js("document.body.style.zoom = '1'"),
         "zoom-in"    => eval_js("document.body.style.zoom = String(parseFloat(document.body.style.zoom || '1') + 0.1)"),
         "zoom-out"   => eval_js("document.body.style.zoom = String(Math.max(0.3, parseFloat(document.body.style.zoom || '1') - 0.1))"),
+        "toggle-frame-chrome" => {
+            if let Some(win) = focused_window(app) {
+                let chrome_state = app.state::<FrameChromeState>();
+                let label = win.label().to_string();
+                let next_style = {
+                    let mut styles = chrome_state.0.lock().unwrap();
+                    let current = styles.get(&label).map(String::as_str).unwrap_or("visible");
+                    let next = match current {
+                        "visible" => "transparent",
+                        "transparent" => "overlay",
+                        _ => "visible",
+                    };
+                    styles.insert(label.clone(), next.to_string());
+                    next.to_string()
+                };
+                let _ = apply_frame_chrome(&win, &next_style);
+            }
+        }
 
         // ── Device presets ──
         "dev-iphone"     => resize_to("iphone"),
@@ -579,6 +1147,7 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
 
 fn main() {
     tauri::Builder::default()
+        .manage(FrameChromeState::default())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
@@ -591,6 +1160,12 @@ fn main() {
             open_feed,
             set_device_frame,
             list_windows,
+            reparent_window,
+            detach_window,
+            capture_frame,
+            capture_all_frames,
+            cursor_position,
+            set_frame_chrome,
         ])
         .setup(|app| {
             println!("⚛ uvspeed v4.0 — Tauri v2");
@@ -606,6 +1181,11 @@ fn main() {
                 }
             }
 
+            // Build and attach the system tray
+            if let Err(e) = build_tray(app) {
+                eprintln!("Tray build error: {}", e);
+            }
+
             // Open DevTools in debug mode
             #[cfg(debug_assertions)]
             {
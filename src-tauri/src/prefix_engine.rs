@@ -2,6 +2,7 @@
 // Tauri IPC bridge to the Rust prefix engine crate
 // Provides high-performance classification via Tauri commands
 
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -10,7 +11,7 @@ use std::path::PathBuf;
 // ──────────────────────────────────────────────────────────
 
 // Re-export the engine for use in main.rs
-pub use uvspeed_prefix_engine::{PrefixClassifier, PrefixSymbol, Category, ClassifyResult};
+pub use uvspeed_prefix_engine::{PrefixClassifier, PrefixSymbol, Category, ClassifyResult, LineState};
 
 // ──────────────────────────────────────────────────────────
 // Tauri IPC Response types
@@ -45,6 +46,30 @@ pub struct BenchmarkResponse {
     pub lines: usize,
     pub elapsed_us: u128,
     pub lines_per_second: f64,
+    pub regressed: bool,
+    pub baseline_median_lps: Option<f64>,
+    pub parallelism: usize,
+}
+
+/// A single recorded benchmark run, appended to the rolling history file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BenchmarkRun {
+    pub timestamp: u64,
+    pub lines: usize,
+    pub elapsed_us: u128,
+    pub lines_per_second: f64,
+    pub cpu_count: usize,
+}
+
+/// A single line whose classification changed since the previous snapshot
+/// of a watched file.
+#[derive(Serialize, Deserialize)]
+pub struct ClassifyLineChange {
+    pub line_num: usize,
+    pub symbol: String,
+    pub category: String,
+    pub bits: u8,
+    pub coords: (i8, i8, i8),
 }
 
 // ──────────────────────────────────────────────────────────
@@ -65,16 +90,60 @@ pub fn classify_line(line: String, _language: Option<String>) -> ClassifyLineRes
     }
 }
 
-/// Classify an entire file by path
-#[tauri::command]
-pub fn classify_file(path: String) -> Result<ClassifyFileResponse, String> {
-    let file_path = PathBuf::from(&path);
-    let content = std::fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+/// Below this many lines, the cheap serial path wins — thread spawn/join
+/// overhead would dwarf the classification work itself.
+const PARALLEL_LINE_THRESHOLD: usize = 50_000;
 
-    let classifier = PrefixClassifier::new();
-    let results = classifier.classify_batch(&content);
+/// Classify `source`, splitting on line boundaries into per-core chunks and
+/// classifying each chunk on the rayon thread pool above
+/// `PARALLEL_LINE_THRESHOLD` lines.
+///
+/// [`LineState`] (block comments, triple-quoted strings, template literals)
+/// can span a chunk boundary, so a naive per-chunk `classify_line` would
+/// misclassify whatever trails the boundary on the large files this path
+/// exists to serve. Instead every chunk is first classified in parallel via
+/// [`PrefixClassifier::classify_batch_from`] assuming a `LineState::Normal`
+/// entry — correct for the common case where nothing is left open at the
+/// join — and a cheap sequential pass then walks the chunk boundaries,
+/// re-classifying (serially, and only) the rare chunk whose predecessor
+/// actually left a non-`Normal` state behind. Returns the results alongside
+/// the degree of parallelism actually used.
+fn classify_batch_auto(classifier: &PrefixClassifier, source: &str) -> (Vec<ClassifyResult>, usize) {
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.len() < PARALLEL_LINE_THRESHOLD {
+        return (classifier.classify_batch(source), 1);
+    }
 
+    let parallelism = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = lines.len().div_ceil(parallelism).max(1);
+
+    let provisional: Vec<(Vec<ClassifyResult>, LineState)> = lines
+        .par_chunks(chunk_size)
+        .map(|chunk| classifier.classify_batch_from(&chunk.join("\n"), LineState::Normal))
+        .collect();
+
+    let mut results = Vec::with_capacity(lines.len());
+    let mut carry = LineState::Normal;
+    for (chunk_idx, (provisional_results, provisional_exit)) in provisional.into_iter().enumerate() {
+        let offset = chunk_idx * chunk_size;
+        let (mut chunk_results, exit_state) = if carry == LineState::Normal {
+            (provisional_results, provisional_exit)
+        } else {
+            let chunk = &lines[offset..(offset + chunk_size).min(lines.len())];
+            classifier.classify_batch_from(&chunk.join("\n"), carry)
+        };
+        for result in &mut chunk_results {
+            result.line_num += offset;
+        }
+        results.extend(chunk_results);
+        carry = exit_state;
+    }
+
+    (results, parallelism)
+}
+
+/// Build a `ClassifyFileResponse` from already-classified lines.
+fn build_classify_response(path: String, results: Vec<ClassifyResult>) -> ClassifyFileResponse {
     let total = results.len();
     let classified = results.iter()
         .filter(|r| r.category != "neutral" && r.category != "unknown")
@@ -85,16 +154,440 @@ pub fn classify_file(path: String) -> Result<ClassifyFileResponse, String> {
         *counts.entry(r.category.clone()).or_insert(0) += 1;
     }
 
-    Ok(ClassifyFileResponse {
+    ClassifyFileResponse {
         path,
         total_lines: total,
         classified_lines: classified,
         coverage: if total > 0 { (classified as f64 / total as f64) * 100.0 } else { 0.0 },
         prefix_counts: counts,
         lines: results,
+    }
+}
+
+/// Classify an entire file by path. Transparently decompresses gzip/zstd
+/// inputs and recurses into `.tar`/`.zip` archives via [`adapter`], returning
+/// one response per extracted member. Each member is classified through the
+/// content-hash keyed [`cache`], so an unchanged tree reclassifies nothing.
+#[tauri::command]
+pub fn classify_file(path: String) -> Result<Vec<ClassifyFileResponse>, String> {
+    let classifier = PrefixClassifier::new();
+    let members = adapter::read_members(&PathBuf::from(&path), 0)?;
+    let disk_cache = cache::shared();
+
+    Ok(members
+        .into_iter()
+        .map(|member| {
+            let key = cache::content_key(&member.content);
+
+            if let Some(mut cached) = disk_cache.read().unwrap().get(&key) {
+                cached.path = member.path;
+                return cached;
+            }
+
+            let (results, _parallelism) = classify_batch_auto(&classifier, &member.content);
+            let response = build_classify_response(member.path, results);
+            disk_cache.write().unwrap().put(&key, &response);
+            response
+        })
+        .collect())
+}
+
+/// Clear every entry from the on-disk classification cache.
+#[tauri::command]
+pub fn clear_classification_cache() {
+    cache::shared().write().unwrap().clear();
+}
+
+/// Inspect the on-disk classification cache: how many entries it holds and
+/// where it lives on disk.
+#[tauri::command]
+pub fn classification_cache_info() -> serde_json::Value {
+    let shared = cache::shared();
+    let guard = shared.read().unwrap();
+    serde_json::json!({
+        "entries": guard.len(),
+        "dir": cache::default_cache_dir().display().to_string(),
     })
 }
 
+/// Start watching a file for modifications, emitting `classify-file-changed`
+/// with only the lines whose classification changed since the last snapshot.
+/// Lets the UI update the gutter live instead of polling `classify_file`.
+#[tauri::command]
+pub fn watch_file(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    watch::start(app, path)
+}
+
+/// Stop watching a previously-watched file and drop its cached state.
+#[tauri::command]
+pub fn unwatch_file(path: String) -> Result<(), String> {
+    watch::stop(&path);
+    Ok(())
+}
+
+// ──────────────────────────────────────────────────────────
+// Live file watching
+// ──────────────────────────────────────────────────────────
+
+/// Registers a filesystem watcher per path and, on each modification,
+/// re-reads the file and emits a Tauri event carrying only the lines whose
+/// classification changed since the previous snapshot.
+mod watch {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::{Mutex, OnceLock};
+
+    use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use tauri::Emitter;
+
+    use super::{ClassifyLineChange, ClassifyResult, PrefixClassifier};
+
+    /// Last-seen content and classification for a watched path, used to
+    /// diff against the next modification.
+    struct WatchedState {
+        last_content: String,
+        last_results: Vec<ClassifyResult>,
+    }
+
+    struct Registration {
+        _watcher: RecommendedWatcher,
+        state: WatchedState,
+    }
+
+    fn registry() -> &'static Mutex<HashMap<String, Registration>> {
+        static REGISTRY: OnceLock<Mutex<HashMap<String, Registration>>> = OnceLock::new();
+        REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    pub fn start(app: tauri::AppHandle, path: String) -> Result<(), String> {
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let classifier = PrefixClassifier::new();
+        let initial_results = classifier.classify_batch(&content);
+
+        let watch_path = path.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let Ok(event) = res else { return };
+            if !matches!(event.kind, EventKind::Modify(_)) {
+                return;
+            }
+            on_modified(&app, &watch_path);
+        })
+        .map_err(|e| format!("Failed to start watcher: {}", e))?;
+
+        watcher
+            .watch(Path::new(&path), RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch '{}': {}", path, e))?;
+
+        registry().lock().unwrap().insert(
+            path,
+            Registration {
+                _watcher: watcher,
+                state: WatchedState {
+                    last_content: content,
+                    last_results: initial_results,
+                },
+            },
+        );
+        Ok(())
+    }
+
+    pub fn stop(path: &str) {
+        registry().lock().unwrap().remove(path);
+    }
+
+    fn on_modified(app: &tauri::AppHandle, path: &str) {
+        let Ok(content) = std::fs::read_to_string(path) else { return };
+        let classifier = PrefixClassifier::new();
+        let new_results = classifier.classify_batch(&content);
+
+        let mut registry = registry().lock().unwrap();
+        let Some(registration) = registry.get_mut(path) else { return };
+        if registration.state.last_content == content {
+            return;
+        }
+
+        let changed = diff_results(&registration.state.last_results, &new_results);
+        registration.state.last_content = content;
+        registration.state.last_results = new_results;
+        drop(registry);
+
+        if !changed.is_empty() {
+            let _ = app.emit(
+                "classify-file-changed",
+                serde_json::json!({ "path": path, "changed": changed }),
+            );
+        }
+    }
+
+    /// Diff old vs new classifications line-by-line, reporting every line
+    /// whose symbol differs (including lines only present in the new file).
+    fn diff_results(old: &[ClassifyResult], new: &[ClassifyResult]) -> Vec<ClassifyLineChange> {
+        new.iter()
+            .enumerate()
+            .filter_map(|(i, result)| {
+                let unchanged = old.get(i).is_some_and(|prev| prev.symbol == result.symbol);
+                if unchanged {
+                    None
+                } else {
+                    Some(ClassifyLineChange {
+                        line_num: i + 1,
+                        symbol: result.symbol.clone(),
+                        category: result.category.clone(),
+                        bits: result.bits,
+                        coords: result.coords,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+// ──────────────────────────────────────────────────────────
+// Persistent classification cache
+// ──────────────────────────────────────────────────────────
+
+/// Content-hash keyed cache modeled on ripgrep-all's `PreprocCache`: on a
+/// hit, classification is skipped entirely and the stored response is
+/// returned; on a miss, the caller classifies and populates it.
+mod cache {
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::{Arc, OnceLock, RwLock};
+
+    use sha2::{Digest, Sha256};
+
+    use super::ClassifyFileResponse;
+
+    /// Baked into the cache key so a classifier taxonomy/format change
+    /// invalidates stale on-disk entries automatically.
+    const CLASSIFIER_VERSION: &str = "prefix-classifier-v1";
+
+    pub trait Cache: Send + Sync {
+        fn get(&self, key: &str) -> Option<ClassifyFileResponse>;
+        fn put(&self, key: &str, entry: &ClassifyFileResponse);
+        fn clear(&self);
+        fn len(&self) -> usize;
+    }
+
+    /// On-disk cache backend: one JSON file per key inside `cache_dir`.
+    pub struct DiskCache {
+        cache_dir: PathBuf,
+    }
+
+    impl DiskCache {
+        pub fn new(cache_dir: PathBuf) -> Self {
+            let _ = fs::create_dir_all(&cache_dir);
+            Self { cache_dir }
+        }
+
+        fn entry_path(&self, key: &str) -> PathBuf {
+            self.cache_dir.join(format!("{}.json", key))
+        }
+    }
+
+    impl Cache for DiskCache {
+        fn get(&self, key: &str) -> Option<ClassifyFileResponse> {
+            let data = fs::read(self.entry_path(key)).ok()?;
+            serde_json::from_slice(&data).ok()
+        }
+
+        fn put(&self, key: &str, entry: &ClassifyFileResponse) {
+            if let Ok(data) = serde_json::to_vec(entry) {
+                if let Ok(mut f) = fs::File::create(self.entry_path(key)) {
+                    let _ = f.write_all(&data);
+                }
+            }
+        }
+
+        fn clear(&self) {
+            if let Ok(entries) = fs::read_dir(&self.cache_dir) {
+                for entry in entries.flatten() {
+                    let _ = fs::remove_file(entry.path());
+                }
+            }
+        }
+
+        fn len(&self) -> usize {
+            fs::read_dir(&self.cache_dir).map(|it| it.count()).unwrap_or(0)
+        }
+    }
+
+    /// Hash file contents plus the classifier version to derive a cache key
+    /// that's invalidated whenever either changes.
+    pub fn content_key(content: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(CLASSIFIER_VERSION.as_bytes());
+        hasher.update(content.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn default_cache_dir() -> PathBuf {
+        std::env::temp_dir().join("uvspeed-classify-cache")
+    }
+
+    /// Process-wide cache handle, lazily opened on first use.
+    pub fn shared() -> Arc<RwLock<dyn Cache>> {
+        static CACHE: OnceLock<Arc<RwLock<dyn Cache>>> = OnceLock::new();
+        CACHE
+            .get_or_init(|| Arc::new(RwLock::new(DiskCache::new(default_cache_dir()))))
+            .clone()
+    }
+}
+
+// ──────────────────────────────────────────────────────────
+// Adapter layer — transparent decompression + archive recursion
+// ──────────────────────────────────────────────────────────
+
+/// Inspects a path/extension and transparently decodes its content before
+/// handing it to `PrefixClassifier::classify_batch`, modeled on ripgrep-all's
+/// `adapter_matcher`/`AdaptInfo` chain of preprocessors.
+mod adapter {
+    use std::io::Read;
+    use std::path::{Path, PathBuf};
+
+    /// Guard against zip-bombs: an archive nested deeper than this is skipped.
+    pub const MAX_ARCHIVE_RECURSION: u32 = 6;
+
+    /// Guard against zip-bombs that stay shallow but expand huge: no single
+    /// member may decompress to more than this many bytes, independent of
+    /// `MAX_ARCHIVE_RECURSION`.
+    pub const MAX_MEMBER_DECOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+    /// Guard against zip-bombs that spread the damage across many sibling
+    /// members instead of one: the sum of every decompression performed
+    /// while servicing a single `read_members` call may not exceed this,
+    /// even when each individual member stays under
+    /// `MAX_MEMBER_DECOMPRESSED_BYTES`.
+    pub const MAX_TOTAL_DECOMPRESSED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+    /// Read `reader` to completion, capped at `MAX_MEMBER_DECOMPRESSED_BYTES`
+    /// so a hostile member can't be decompressed fully into memory before we
+    /// notice it's oversized, and debits the result from `budget` so the
+    /// running total across every member in the archive stays bounded too.
+    fn read_to_end_bounded<R: Read>(reader: R, what: &str, budget: &mut u64) -> Result<Vec<u8>, String> {
+        let mut buf = Vec::new();
+        reader
+            .take(MAX_MEMBER_DECOMPRESSED_BYTES + 1)
+            .read_to_end(&mut buf)
+            .map_err(|e| format!("Failed to decompress {}: {}", what, e))?;
+        if buf.len() as u64 > MAX_MEMBER_DECOMPRESSED_BYTES {
+            return Err(format!(
+                "{} exceeds the {}-byte decompressed member limit",
+                what, MAX_MEMBER_DECOMPRESSED_BYTES
+            ));
+        }
+        *budget = budget.checked_sub(buf.len() as u64).ok_or_else(|| {
+            format!(
+                "decompressing {} would exceed the {}-byte total archive limit",
+                what, MAX_TOTAL_DECOMPRESSED_BYTES
+            )
+        })?;
+        Ok(buf)
+    }
+
+    /// One extracted, decoded text member ready for classification.
+    pub struct Member {
+        pub path: String,
+        pub content: String,
+    }
+
+    /// Read a path, transparently decompressing and recursing into archives
+    /// as needed, and return every extracted text member.
+    pub fn read_members(path: &Path, archive_recursion_depth: u32) -> Result<Vec<Member>, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let mut budget = MAX_TOTAL_DECOMPRESSED_BYTES;
+        decode_bytes(path, bytes, archive_recursion_depth, &mut budget)
+    }
+
+    fn decode_bytes(path: &Path, bytes: Vec<u8>, depth: u32, budget: &mut u64) -> Result<Vec<Member>, String> {
+        if depth > MAX_ARCHIVE_RECURSION {
+            return Ok(vec![]);
+        }
+
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        match ext.as_str() {
+            "gz" | "gzip" => {
+                let decoder = flate2::read::GzDecoder::new(bytes.as_slice());
+                let decompressed = read_to_end_bounded(decoder, "gzip", budget)?;
+                decode_bytes(&strip_extension(path), decompressed, depth + 1, budget)
+            }
+            "zst" | "zstd" => {
+                let decoder = zstd::stream::read::Decoder::new(bytes.as_slice())
+                    .map_err(|e| format!("Failed to decompress zstd: {}", e))?;
+                let decompressed = read_to_end_bounded(decoder, "zstd", budget)?;
+                decode_bytes(&strip_extension(path), decompressed, depth + 1, budget)
+            }
+            "zip" => read_zip(bytes, depth + 1, budget),
+            "tar" => read_tar(bytes.as_slice(), depth + 1, budget),
+            _ if path_ends_with(path, ".tar.gz") || path_ends_with(path, ".tgz") => {
+                let decoder = flate2::read::GzDecoder::new(bytes.as_slice());
+                let decompressed = read_to_end_bounded(decoder, "gzip", budget)?;
+                read_tar(decompressed.as_slice(), depth + 1, budget)
+            }
+            _ => match String::from_utf8(bytes) {
+                Ok(content) => Ok(vec![Member {
+                    path: path.display().to_string(),
+                    content,
+                }]),
+                Err(_) => Ok(vec![]),
+            },
+        }
+    }
+
+    fn read_zip(bytes: Vec<u8>, depth: u32, budget: &mut u64) -> Result<Vec<Member>, String> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+            .map_err(|e| format!("Failed to open zip: {}", e))?;
+
+        let mut members = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| format!("Failed to read zip entry {}: {}", i, e))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let entry_path = PathBuf::from(entry.name());
+            let what = format!("zip entry {}", i);
+            let data = read_to_end_bounded(&mut entry, &what, budget)?;
+            members.extend(decode_bytes(&entry_path, data, depth, budget)?);
+        }
+        Ok(members)
+    }
+
+    fn read_tar(bytes: &[u8], depth: u32, budget: &mut u64) -> Result<Vec<Member>, String> {
+        let mut archive = tar::Archive::new(bytes);
+        let mut members = Vec::new();
+        for entry in archive
+            .entries()
+            .map_err(|e| format!("Failed to read tar: {}", e))?
+        {
+            let mut entry = entry.map_err(|e| format!("Failed to read tar entry: {}", e))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let entry_path = entry.path().map_err(|e| format!("{}", e))?.into_owned();
+            let data = read_to_end_bounded(&mut entry, "tar entry", budget)?;
+            members.extend(decode_bytes(&entry_path, data, depth, budget)?);
+        }
+        Ok(members)
+    }
+
+    fn strip_extension(path: &Path) -> PathBuf {
+        path.with_extension("")
+    }
+
+    fn path_ends_with(path: &Path, suffix: &str) -> bool {
+        path.to_string_lossy().to_lowercase().ends_with(suffix)
+    }
+}
+
 /// Batch classify multiple lines
 #[tauri::command]
 pub fn classify_lines(lines: Vec<String>) -> Vec<ClassifyLineResponse> {
@@ -110,6 +603,81 @@ pub fn classify_lines(lines: Vec<String>) -> Vec<ClassifyLineResponse> {
     }).collect()
 }
 
+/// Magic bytes identifying a packed classification buffer.
+const PACKED_MAGIC: [u8; 4] = *b"QPX1";
+const PACKED_VERSION: u8 = 1;
+
+/// Pack classification results into a compact binary wire format: a small
+/// header (magic, version, line count) followed by one byte per line (the
+/// symbol's `to_bits()`), since category is always derivable from the
+/// symbol. A million-line file serializes to ~1 MB instead of tens of MB
+/// of JSON.
+fn pack_classify_results(results: &[ClassifyResult]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(4 + 1 + 4 + results.len());
+    buf.extend_from_slice(&PACKED_MAGIC);
+    buf.push(PACKED_VERSION);
+    buf.extend_from_slice(&(results.len() as u32).to_le_bytes());
+    buf.extend(results.iter().map(|r| r.bits));
+    buf
+}
+
+/// Decode a packed buffer back into `(PrefixSymbol, Category)` pairs —
+/// the same format the frontend decoder reads.
+pub fn unpack_classify_results(buf: &[u8]) -> Result<Vec<(PrefixSymbol, Category)>, String> {
+    if buf.len() < 9 || buf[0..4] != PACKED_MAGIC {
+        return Err("Not a packed classification buffer".to_string());
+    }
+    if buf[4] != PACKED_VERSION {
+        return Err(format!("Unsupported packed version: {}", buf[4]));
+    }
+    let line_count = u32::from_le_bytes(buf[5..9].try_into().unwrap()) as usize;
+    let bits = buf.get(9..9 + line_count).ok_or("Truncated packed buffer")?;
+
+    Ok(bits
+        .iter()
+        .map(|&b| {
+            let symbol = PrefixSymbol::from_bits(b);
+            (symbol, category_for_symbol(symbol))
+        })
+        .collect())
+}
+
+/// Inverse of `Category::symbol()` — every symbol maps to exactly one category.
+fn category_for_symbol(symbol: PrefixSymbol) -> Category {
+    match symbol {
+        PrefixSymbol::PlusOne => Category::Declaration,
+        PrefixSymbol::One => Category::Logic,
+        PrefixSymbol::MinusOne => Category::IO,
+        PrefixSymbol::PlusZero => Category::Assignment,
+        PrefixSymbol::Zero => Category::Neutral,
+        PrefixSymbol::MinusZero => Category::Comment,
+        PrefixSymbol::PlusN => Category::Modifier,
+        PrefixSymbol::N => Category::Import,
+        PrefixSymbol::MinusN => Category::Unknown,
+    }
+}
+
+/// Classify an entire file and return the compact packed binary encoding.
+#[tauri::command]
+pub fn classify_file_packed(path: String) -> Result<Vec<u8>, String> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let classifier = PrefixClassifier::new();
+    Ok(pack_classify_results(&classifier.classify_batch(&content)))
+}
+
+/// Batch classify multiple lines and return the compact packed binary encoding.
+#[tauri::command]
+pub fn classify_lines_packed(lines: Vec<String>) -> Vec<u8> {
+    let classifier = PrefixClassifier::new();
+    let results: Vec<ClassifyResult> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| classifier.classify_line(line, i + 1))
+        .collect();
+    pack_classify_results(&results)
+}
+
 /// Generate gutter strings for source content
 #[tauri::command]
 pub fn generate_gutter(source: String) -> GutterResponse {
@@ -119,16 +687,20 @@ pub fn generate_gutter(source: String) -> GutterResponse {
     GutterResponse { gutter, total_lines: total }
 }
 
-/// Benchmark the classifier (classify N lines, return timing)
+/// Benchmark the classifier (classify N lines, return timing), appending the
+/// run to the rolling history and flagging a regression against the
+/// trailing median.
 #[tauri::command]
 pub fn benchmark_classifier(source: String, iterations: Option<u32>) -> BenchmarkResponse {
     let classifier = PrefixClassifier::new();
     let iters = iterations.unwrap_or(100);
     let line_count = source.lines().count();
 
+    let mut parallelism = 1;
     let start = std::time::Instant::now();
     for _ in 0..iters {
-        let _ = classifier.classify_batch(&source);
+        let (_, p) = classify_batch_auto(&classifier, &source);
+        parallelism = p;
     }
     let elapsed = start.elapsed();
 
@@ -140,9 +712,293 @@ pub fn benchmark_classifier(source: String, iterations: Option<u32>) -> Benchmar
         0.0
     };
 
+    let run = BenchmarkRun {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        lines: total_lines,
+        elapsed_us,
+        lines_per_second: lps,
+        cpu_count: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+    };
+
+    let (baseline_median_lps, regressed) = bench_history::record(run);
+
     BenchmarkResponse {
         lines: total_lines,
         elapsed_us,
         lines_per_second: lps,
+        regressed,
+        baseline_median_lps,
+        parallelism,
+    }
+}
+
+/// Read back the rolling benchmark history (oldest first) for charting.
+#[tauri::command]
+pub fn benchmark_history() -> Vec<BenchmarkRun> {
+    bench_history::read()
+}
+
+// ──────────────────────────────────────────────────────────
+// Rolling benchmark history
+// ──────────────────────────────────────────────────────────
+
+/// Appends each benchmark run to a JSON history file capped at the most
+/// recent N entries, and flags whether the latest run regressed versus the
+/// trailing median throughput by more than a fixed threshold.
+mod bench_history {
+    use super::BenchmarkRun;
+    use std::path::PathBuf;
+
+    /// Keep only this many most-recent runs.
+    const MAX_HISTORY: usize = 20;
+    /// A run is flagged as regressed if it's this much slower than the
+    /// trailing median (e.g. 0.15 = 15% slower).
+    const REGRESSION_THRESHOLD: f64 = 0.15;
+
+    fn history_path() -> PathBuf {
+        std::env::temp_dir().join("uvspeed-benchmark-history.json")
+    }
+
+    pub fn read() -> Vec<BenchmarkRun> {
+        std::fs::read(history_path())
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(runs: &[BenchmarkRun]) {
+        if let Ok(data) = serde_json::to_vec(runs) {
+            let _ = std::fs::write(history_path(), data);
+        }
+    }
+
+    fn median_lps(runs: &[BenchmarkRun]) -> Option<f64> {
+        if runs.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = runs.iter().map(|r| r.lines_per_second).collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mid = sorted.len() / 2;
+        Some(if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        })
+    }
+
+    /// Append `run` to the history (capped at `MAX_HISTORY`), returning the
+    /// trailing median throughput (computed before this run was added) and
+    /// whether this run regressed against it.
+    pub fn record(run: BenchmarkRun) -> (Option<f64>, bool) {
+        let mut history = read();
+        let baseline_median = median_lps(&history);
+
+        history.push(run.clone());
+        if history.len() > MAX_HISTORY {
+            let overflow = history.len() - MAX_HISTORY;
+            history.drain(0..overflow);
+        }
+        write(&history);
+
+        let regressed = baseline_median
+            .map(|median| run.lines_per_second < median * (1.0 - REGRESSION_THRESHOLD))
+            .unwrap_or(false);
+
+        (baseline_median, regressed)
+    }
+}
+
+// ──────────────────────────────────────────────────────────
+// Inverted-index / ranked query over a classified corpus
+// ──────────────────────────────────────────────────────────
+
+/// A query hit: a file and the contiguous line ranges where the queried
+/// categories occur, ranked by a tf-idf style score.
+#[derive(Serialize, Deserialize)]
+pub struct QueryHit {
+    pub path: String,
+    pub score: f64,
+    pub line_ranges: Vec<(usize, usize)>,
+}
+
+/// Build (or replace) the process-wide corpus index from a set of file
+/// paths, classifying each and recording every line's category plus
+/// per-category line counts for each file.
+#[tauri::command]
+pub fn build_index(paths: Vec<String>) -> Result<serde_json::Value, String> {
+    let classifier = PrefixClassifier::new();
+    let mut builder = index::IndexBuilder::new();
+
+    for path in &paths {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+        let results = classifier.classify_batch(&content);
+        builder.add_file(path.clone(), &results);
+    }
+
+    let file_count = builder.file_count();
+    index::set_shared(builder.build());
+
+    Ok(serde_json::json!({ "indexed_files": file_count }))
+}
+
+/// Rank files by how dominated they are by the queried categories, using a
+/// tf-idf style score, and return the top `top_k` with matching line ranges.
+#[tauri::command]
+pub fn query(categories: Vec<String>, top_k: usize) -> Vec<QueryHit> {
+    match index::shared_index().as_ref() {
+        Some(idx) => idx.query(&categories, top_k),
+        None => Vec::new(),
+    }
+}
+
+/// Searchable index over a classified corpus: for each file, the category of
+/// every line plus per-category line counts (term frequencies), enabling a
+/// "which files are dominated by category X" style ranked query. There are
+/// no per-category postings lists; a query scans every indexed file.
+mod index {
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    use super::{ClassifyResult, QueryHit};
+
+    struct IndexedFile {
+        path: String,
+        line_count: usize,
+        /// Category assigned to each line, in order.
+        line_categories: Vec<String>,
+        /// How many lines of this file fall in each category.
+        category_counts: HashMap<String, usize>,
+    }
+
+    pub struct IndexBuilder {
+        files: Vec<IndexedFile>,
+    }
+
+    impl IndexBuilder {
+        pub fn new() -> Self {
+            Self { files: Vec::new() }
+        }
+
+        pub fn add_file(&mut self, path: String, results: &[ClassifyResult]) {
+            let line_categories: Vec<String> = results.iter().map(|r| r.category.clone()).collect();
+            let mut category_counts: HashMap<String, usize> = HashMap::new();
+            for category in &line_categories {
+                *category_counts.entry(category.clone()).or_insert(0) += 1;
+            }
+            self.files.push(IndexedFile {
+                path,
+                line_count: line_categories.len(),
+                line_categories,
+                category_counts,
+            });
+        }
+
+        pub fn file_count(&self) -> usize {
+            self.files.len()
+        }
+
+        pub fn build(self) -> CorpusIndex {
+            CorpusIndex { files: self.files }
+        }
+    }
+
+    impl Default for IndexBuilder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    pub struct CorpusIndex {
+        files: Vec<IndexedFile>,
+    }
+
+    impl CorpusIndex {
+        /// Rank files by a tf-idf style score over the queried categories:
+        /// tf = (count of category in file) / (file line count),
+        /// idf = ln(total_files / files_containing_category),
+        /// score = sum of tf*idf over queried categories.
+        pub fn query(&self, categories: &[String], top_k: usize) -> Vec<QueryHit> {
+            let total_files = self.files.len().max(1) as f64;
+
+            let idf: HashMap<&str, f64> = categories
+                .iter()
+                .map(|category| {
+                    let containing = self
+                        .files
+                        .iter()
+                        .filter(|f| f.category_counts.contains_key(category))
+                        .count()
+                        .max(1) as f64;
+                    (category.as_str(), (total_files / containing).ln())
+                })
+                .collect();
+
+            let mut scored: Vec<QueryHit> = self
+                .files
+                .iter()
+                .filter_map(|file| {
+                    if file.line_count == 0 {
+                        return None;
+                    }
+                    let score: f64 = categories
+                        .iter()
+                        .map(|category| {
+                            let tf = *file.category_counts.get(category).unwrap_or(&0) as f64
+                                / file.line_count as f64;
+                            tf * idf.get(category.as_str()).copied().unwrap_or(0.0)
+                        })
+                        .sum();
+                    if score <= 0.0 {
+                        return None;
+                    }
+                    Some(QueryHit {
+                        path: file.path.clone(),
+                        score,
+                        line_ranges: merge_line_ranges(&file.line_categories, categories),
+                    })
+                })
+                .collect();
+
+            scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+            scored.truncate(top_k);
+            scored
+        }
+    }
+
+    /// Merge contiguous lines whose category is one of `categories` into
+    /// `(start_line, end_line)` ranges (1-indexed, inclusive).
+    fn merge_line_ranges(line_categories: &[String], categories: &[String]) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut run_start: Option<usize> = None;
+
+        for (i, category) in line_categories.iter().enumerate() {
+            let matches = categories.iter().any(|c| c == category);
+            match (matches, run_start) {
+                (true, None) => run_start = Some(i + 1),
+                (false, Some(start)) => {
+                    ranges.push((start, i));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = run_start {
+            ranges.push((start, line_categories.len()));
+        }
+        ranges
+    }
+
+    pub fn set_shared(idx: CorpusIndex) {
+        shared_index().replace(idx);
+    }
+
+    /// Lock the process-wide index for reading or replacing.
+    pub fn shared_index() -> std::sync::MutexGuard<'static, Option<CorpusIndex>> {
+        static INDEX: OnceLock<Mutex<Option<CorpusIndex>>> = OnceLock::new();
+        INDEX.get_or_init(|| Mutex::new(None)).lock().unwrap()
     }
 }